@@ -0,0 +1,86 @@
+//! Counts the allocations made while parsing a large, `Cargo.lock`-like document, along
+//! with how long parsing takes. Run with:
+//!
+//! ```sh
+//! cargo run --release --example allocations
+//! ```
+
+use {
+	boml::prelude::*,
+	std::{
+		alloc::{GlobalAlloc, Layout, System},
+		fmt::Write,
+		sync::atomic::{AtomicUsize, Ordering},
+		time::Instant,
+	},
+};
+
+/// Counts every allocation and reallocation, then hands them to the system allocator.
+struct CountingAllocator;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAllocator {
+	unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+		ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+		System.alloc(layout)
+	}
+
+	unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+		System.dealloc(ptr, layout)
+	}
+
+	unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+		ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+		System.realloc(ptr, layout, new_size)
+	}
+}
+
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+const PACKAGES: usize = 5_000;
+const FEATURES: usize = 500;
+const RUNS: usize = 20;
+
+fn main() {
+	let mut source = String::from("version = 3\n\n[features]\nfull = [\n");
+	for idx in 0..FEATURES {
+		writeln!(source, "\t\"feature-{idx}\",").unwrap();
+	}
+	source.push_str("]\n");
+	for idx in 0..PACKAGES {
+		write!(
+			source,
+			"\n[[package]]\n\
+			 name = \"package-{idx}\"\n\
+			 version = \"1.{}.{}\"\n\
+			 source = \"registry+https://github.com/rust-lang/crates.io-index\"\n\
+			 checksum = \"{idx:064x}\"\n\
+			 dependencies = [\n \"package-{}\",\n \"package-{}\",\n \"package-{}\",\n]\n",
+			idx % 40,
+			idx % 7,
+			idx / 2,
+			idx / 3,
+			idx / 5,
+		)
+		.unwrap();
+	}
+
+	let before = ALLOCATIONS.load(Ordering::Relaxed);
+	let toml = Toml::parse(&source).unwrap();
+	let allocations = ALLOCATIONS.load(Ordering::Relaxed) - before;
+	assert_eq!(toml.get_array("package").unwrap().len(), PACKAGES);
+	drop(toml);
+
+	let start = Instant::now();
+	for _ in 0..RUNS {
+		std::hint::black_box(Toml::parse(std::hint::black_box(&source)).unwrap());
+	}
+	let elapsed = start.elapsed() / RUNS as u32;
+
+	println!(
+		"Parsed {} KiB with {PACKAGES} tables in {elapsed:?}, with {allocations} allocations",
+		source.len() / 1024,
+	);
+}
@@ -0,0 +1,21 @@
+//! Renders a boml parsing error with miette. Run with:
+//!
+//! ```sh
+//! cargo run --example miette --features miette
+//! ```
+
+use {boml::prelude::*, miette::Report};
+
+const SOURCE: &str = r#"[package]
+name = "example"
+version = "0.1.0"
+
+[dependencies]
+boml = { version = "0.3" features = ["miette"] }
+"#;
+
+fn main() {
+	let err = Toml::parse(SOURCE).unwrap_err();
+	let report = Report::from(err.with_file_name("Cargo.toml"));
+	println!("{report:?}");
+}
@@ -0,0 +1,118 @@
+//! Compares boml's whitespace and bare key scanning with the simple byte-at-a-time loops
+//! they replaced, on a document with deep indentation and long bare keys. Run with:
+//!
+//! ```sh
+//! cargo run --release --example scanning
+//! ```
+
+use {
+	boml::{
+		prelude::*,
+		text::{is_bare_key_byte, Text},
+	},
+	std::{fmt::Write, hint::black_box, time::Instant},
+};
+
+const LINES: usize = 100_000;
+const RUNS: usize = 30;
+
+/// Runs `scan` from each of `starts`, and returns the fastest run's time.
+fn bench(source: &str, starts: &[usize], scan: fn(&str, usize) -> usize) -> u128 {
+	(0..RUNS)
+		.map(|_| {
+			let start = Instant::now();
+			for idx in starts {
+				black_box(scan(black_box(source), *idx));
+			}
+			start.elapsed().as_micros()
+		})
+		.min()
+		.unwrap()
+}
+
+// The scanners aren't inlined, so each one is measured the same way, like they're used
+// in the parser: on short runs, from many places in the document.
+
+#[inline(never)]
+fn bytewise_whitespace(source: &str, mut idx: usize) -> usize {
+	let bytes = source.as_bytes();
+	while let Some(b' ' | b'\t' | b'\n' | b'\r') = bytes.get(idx) {
+		idx += 1;
+	}
+	idx
+}
+#[inline(never)]
+fn boml_whitespace(source: &str, idx: usize) -> usize {
+	let mut text = Text {
+		text: source,
+		idx,
+		..Default::default()
+	};
+	text.skip_whitespace_and_newlines();
+	text.idx
+}
+
+#[inline(never)]
+fn bytewise_bare_key(source: &str, mut idx: usize) -> usize {
+	let bytes = source.as_bytes();
+	while let Some(byte) = bytes.get(idx) {
+		if !byte.is_ascii_alphanumeric() && *byte != b'-' && *byte != b'_' {
+			break;
+		}
+		idx += 1;
+	}
+	idx
+}
+#[inline(never)]
+fn boml_bare_key(source: &str, mut idx: usize) -> usize {
+	let bytes = source.as_bytes();
+	while bytes.get(idx).copied().is_some_and(is_bare_key_byte) {
+		idx += 1;
+	}
+	idx
+}
+
+fn main() {
+	let mut source = String::new();
+	let mut indents = Vec::with_capacity(LINES);
+	let mut keys = Vec::with_capacity(LINES);
+	for idx in 0..LINES {
+		if idx % 20 == 0 {
+			writeln!(source, "[section-{idx}]").unwrap();
+		}
+		indents.push(source.len());
+		source.push_str(&" ".repeat(4 * (idx % 5)));
+		keys.push(source.len());
+		writeln!(source, "a-fairly_long-bare-key-name-{idx} = {idx}").unwrap();
+	}
+
+	for (start, end) in indents.iter().zip(&keys) {
+		assert_eq!(bytewise_whitespace(&source, *start), *end);
+		assert_eq!(boml_whitespace(&source, *start), *end);
+		assert_eq!(
+			bytewise_bare_key(&source, *end),
+			boml_bare_key(&source, *end)
+		);
+	}
+
+	println!(
+		"Whitespace: {}us byte-at-a-time, {}us in boml",
+		bench(&source, &indents, bytewise_whitespace),
+		bench(&source, &indents, boml_whitespace)
+	);
+	println!(
+		"Bare keys: {}us byte-at-a-time, {}us in boml",
+		bench(&source, &keys, bytewise_bare_key),
+		bench(&source, &keys, boml_bare_key)
+	);
+
+	let parse = (0..RUNS)
+		.map(|_| {
+			let start = Instant::now();
+			black_box(Toml::parse(black_box(&source)).unwrap());
+			start.elapsed()
+		})
+		.min()
+		.unwrap();
+	println!("Parsing the whole document: {parse:?}");
+}
@@ -0,0 +1,56 @@
+//! Times parsing a large, `Cargo.lock`-like document, which has thousands of small tables.
+//! Compares the default hasher with [`ParseOptions::fast_hash()`]:
+//!
+//! ```sh
+//! cargo run --release --example table_hashing
+//! ```
+
+use {
+	boml::prelude::*,
+	std::{fmt::Write, time::Instant},
+};
+
+const PACKAGES: usize = 5_000;
+const RUNS: usize = 20;
+
+fn main() {
+	let mut source = String::from("version = 3\n");
+	for idx in 0..PACKAGES {
+		write!(
+			source,
+			"\n[[package]]\n\
+			 name = \"package-{idx}\"\n\
+			 version = \"1.{}.{}\"\n\
+			 source = \"registry+https://github.com/rust-lang/crates.io-index\"\n\
+			 checksum = \"{idx:064x}\"\n\
+			 dependencies = [\n \"package-{}\",\n \"package-{}\",\n]\n",
+			idx % 40,
+			idx % 7,
+			idx / 2,
+			idx / 3,
+		)
+		.unwrap();
+	}
+
+	for fast in [false, true] {
+		let options = ParseOptions::new().fast_hash(fast);
+
+		// Warm up, and make sure the document is valid
+		let toml = Toml::parse_with(&source, &options).unwrap();
+		assert_eq!(toml.get_array("package").unwrap().len(), PACKAGES);
+
+		let start = Instant::now();
+		for _ in 0..RUNS {
+			std::hint::black_box(
+				Toml::parse_with(std::hint::black_box(&source), &options).unwrap(),
+			);
+		}
+		let elapsed = start.elapsed() / RUNS as u32;
+
+		println!(
+			"Parsed {} KiB with {PACKAGES} tables in {elapsed:?} (fast hash {})",
+			source.len() / 1024,
+			if fast { "enabled" } else { "disabled" }
+		);
+	}
+}
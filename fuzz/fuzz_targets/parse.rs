@@ -0,0 +1,37 @@
+//! Parses arbitrary text with every parser boml has, and renders any errors. None of them
+//! should ever panic.
+//!
+//! Run with `cargo +nightly fuzz run parse` from the repository's root. Crashes that this
+//! finds should be minimized with `cargo fuzz tmin` and added to `tests/fuzz.rs`.
+
+#![no_main]
+
+use {boml::prelude::*, libfuzzer_sys::fuzz_target};
+
+fuzz_target!(|source: &str| {
+	if let Err(err) = Toml::parse(source) {
+		let _ = err.to_string();
+		let _ = err.to_owned_error();
+	}
+
+	let (_, errors) = Toml::parse_lenient(source);
+	for err in errors {
+		let _ = err.to_string();
+	}
+
+	let options = ParseOptions::new()
+		.toml_version(TomlVersion::V1_1)
+		.capture_comments(true);
+	match Toml::parse_with(source, &options) {
+		Ok(toml) => {
+			for comment in toml.comments() {
+				let _ = comment.text();
+			}
+		}
+		Err(err) => {
+			let _ = err.to_string();
+		}
+	}
+
+	let _ = TomlDocument::parse(source);
+});
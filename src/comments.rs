@@ -0,0 +1,104 @@
+//! Comments captured while parsing, when [`ParseOptions::capture_comments()`] is enabled.
+//!
+//! [`ParseOptions::capture_comments()`]: crate::options::ParseOptions::capture_comments()
+
+use crate::text::{Span, Text};
+
+/// A comment from a TOML document. See [`Toml::comments()`].
+///
+/// [`Toml::comments()`]: crate::Toml::comments()
+#[derive(Clone, Copy)]
+pub struct Comment<'a> {
+	/// The entire comment, starting at the `#` and stopping before the line ending.
+	pub span: Span<'a>,
+	/// Where the comment is, relative to the item it belongs to.
+	pub placement: CommentPlacement,
+	/// The item the comment belongs to: the entire header of a table, or the key of a
+	/// key/value pair. This is `None` for comments that aren't next to an item, like
+	/// comments at the end of the document.
+	pub item: Option<Span<'a>>,
+}
+impl<'a> Comment<'a> {
+	/// The comment's text, without the `#` or surrounding whitespace.
+	pub fn text(&self) -> &'a str {
+		self.span.to_str()[1..].trim()
+	}
+}
+impl std::fmt::Debug for Comment<'_> {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		f.debug_struct("Comment")
+			.field("text", &self.span.as_str())
+			.field("placement", &self.placement)
+			.field("item", &self.item.as_ref().map(Span::as_str))
+			.finish()
+	}
+}
+
+/// Where a [`Comment`] is, relative to the item it belongs to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CommentPlacement {
+	/// The comment is on its own line, before the item.
+	Leading,
+	/// The comment is at the end of the item's line.
+	Trailing,
+}
+
+/// Collects comments as the document is parsed, and attaches them to items.
+#[derive(Default)]
+pub(crate) struct CommentCapture<'a> {
+	/// Every comment found so far.
+	pub comments: Vec<Comment<'a>>,
+	/// Where the comments that are waiting for the next item start in `comments`.
+	pending: usize,
+	/// The last item that was parsed, and the index right after it.
+	last_item: Option<(Span<'a>, usize)>,
+}
+impl<'a> CommentCapture<'a> {
+	/// Records the comment starting at the current index, without moving past it.
+	pub fn comment(&mut self, text: &Text<'a>) {
+		let start = text.idx;
+		let mut end = text.excerpt(start..).find(b'\n').unwrap_or(text.text.len());
+		if text.byte(end - 1) == Some(b'\r') {
+			end -= 1;
+		}
+		let span = text.excerpt(start..end);
+
+		// Comments on the same line as the last item are trailing comments
+		let trailing = self.last_item.filter(|&(_, item_end)| {
+			item_end <= start && !text.text.as_bytes()[item_end..start].contains(&b'\n')
+		});
+		let comment = match trailing {
+			Some((item, _)) => Comment {
+				span,
+				placement: CommentPlacement::Trailing,
+				item: Some(item),
+			},
+			None => Comment {
+				span,
+				placement: CommentPlacement::Leading,
+				item: None,
+			},
+		};
+		self.comments.push(comment);
+		if trailing.is_some() {
+			self.pending = self.comments.len();
+		}
+	}
+
+	/// Attaches the pending comments to an item that was just parsed, which ends right
+	/// before `end`.
+	pub fn item(&mut self, item: Span<'a>, end: usize) {
+		for comment in &mut self.comments[self.pending..] {
+			comment.item = Some(item);
+		}
+		self.pending = self.comments.len();
+		self.last_item = Some((item, end));
+	}
+
+	/// Drops the pending comments after an item failed to parse, leaving them without
+	/// an item.
+	pub fn error(&mut self) {
+		self.pending = self.comments.len();
+		self.last_item = None;
+	}
+}
@@ -0,0 +1,193 @@
+//! [`miette`] integration, so parsing errors can be rendered as fancy diagnostics. Only
+//! compiled with the `miette` feature.
+
+use {
+	crate::{crate_prelude::*, NamedError},
+	miette::{
+		Diagnostic, LabeledSpan, MietteError, MietteSpanContents, Report, SourceCode, SourceSpan,
+		SpanContents,
+	},
+	std::fmt::{self, Display},
+};
+
+/// A parsing [`Error`] as a [`miette`] diagnostic. Create one with
+/// [`Error::into_diagnostic()`] or [`NamedError::into_diagnostic()`].
+///
+/// Errors are wrapped instead of implementing [`Diagnostic`] directly because an
+/// [`Error`]'s [`Display`] implementation already renders the source snippet, which
+/// would be duplicated by miette. This only displays the error's message, and leaves the
+/// rest to miette.
+#[derive(Debug)]
+pub struct TomlDiagnostic<'a> {
+	/// The error.
+	pub error: Error<'a>,
+	/// The name of the file the error came from, if it's known.
+	pub name: Option<String>,
+}
+impl Display for TomlDiagnostic<'_> {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		Display::fmt(&self.error.kind, f)
+	}
+}
+impl std::error::Error for TomlDiagnostic<'_> {}
+impl Diagnostic for TomlDiagnostic<'_> {
+	fn code<'a>(&'a self) -> Option<Box<dyn Display + 'a>> {
+		Some(Box::new(self.error.kind.code()))
+	}
+
+	fn help<'a>(&'a self) -> Option<Box<dyn Display + 'a>> {
+		if let Some(word) = self.error.unquoted_string() {
+			return Some(Box::new(format!(
+				"string values must be quoted, like `\"{word}\"`"
+			)));
+		}
+		help(self.error.kind).map(|help| Box::new(help) as Box<dyn Display>)
+	}
+
+	fn source_code(&self) -> Option<&dyn SourceCode> {
+		Some(self)
+	}
+
+	fn labels(&self) -> Option<Box<dyn Iterator<Item = LabeledSpan> + '_>> {
+		Some(Box::new(std::iter::once(label(&self.error))))
+	}
+}
+/// Diagnostics are their own source code, so the file's name can be attached to the
+/// source.
+impl SourceCode for TomlDiagnostic<'_> {
+	fn read_span<'a>(
+		&'a self,
+		span: &SourceSpan,
+		context_lines_before: usize,
+		context_lines_after: usize,
+	) -> Result<Box<dyn SpanContents<'a> + 'a>, MietteError> {
+		let contents =
+			self.error
+				.src
+				.source
+				.read_span(span, context_lines_before, context_lines_after)?;
+		let Some(ref name) = self.name else {
+			return Ok(contents);
+		};
+
+		Ok(Box::new(MietteSpanContents::new_named(
+			name.clone(),
+			contents.data(),
+			*contents.span(),
+			contents.line(),
+			contents.column(),
+			contents.line_count(),
+		)))
+	}
+}
+
+impl<'a> From<Error<'a>> for TomlDiagnostic<'a> {
+	fn from(error: Error<'a>) -> Self {
+		Self { error, name: None }
+	}
+}
+impl<'a> From<NamedError<'a>> for TomlDiagnostic<'a> {
+	fn from(named: NamedError<'a>) -> Self {
+		Self {
+			error: named.error,
+			name: Some(named.name),
+		}
+	}
+}
+impl From<Error<'static>> for Report {
+	fn from(error: Error<'static>) -> Self {
+		Report::new(TomlDiagnostic::from(error))
+	}
+}
+impl From<NamedError<'static>> for Report {
+	fn from(named: NamedError<'static>) -> Self {
+		Report::new(TomlDiagnostic::from(named))
+	}
+}
+
+impl<'a> Error<'a> {
+	/// Converts this error into a [`miette`] diagnostic.
+	pub fn into_diagnostic(self) -> TomlDiagnostic<'a> {
+		self.into()
+	}
+}
+impl<'a> NamedError<'a> {
+	/// Converts this error into a [`miette`] diagnostic, which shows the file's name with
+	/// the source snippet.
+	pub fn into_diagnostic(self) -> TomlDiagnostic<'a> {
+		self.into()
+	}
+}
+
+/// Labels the error's span with a short description of the error.
+fn label(err: &Error<'_>) -> LabeledSpan {
+	// Diagnostics only highlight the first line of the span, like boml's own errors
+	let text = err.src.clamped_str();
+	let len = text
+		.find(['\r', '\n'])
+		.filter(|len| *len > 0)
+		.unwrap_or(text.len());
+	let start = err.src.clamped_start();
+
+	LabeledSpan::new_primary_with_span(Some(err.kind.to_string()), (start, len))
+}
+
+/// Suggestions for fixing each kind of error.
+fn help(kind: ErrorKind) -> Option<&'static str> {
+	Some(match kind {
+		ErrorKind::InvalidBareKey => {
+			"bare keys can only contain ASCII letters, digits, `-`, and `_`; put the key in \
+			 quotes to use other characters"
+		}
+		ErrorKind::BareKeyHasSpace => "put the key in quotes, or replace the space with `_`",
+		ErrorKind::NoEqualsInAssignment => "keys must be followed by `= <value>`",
+		ErrorKind::NoKeyInAssignment | ErrorKind::LeadingDotInKey => {
+			"add a key before the `=`, like `key = value`"
+		}
+		ErrorKind::NoValueInAssignment => "add a value after the `=`",
+		ErrorKind::UnclosedString => "add a closing quote to the end of the string",
+		ErrorKind::UnrecognisedValue => {
+			"if this is meant to be a string, put it in quotes; otherwise, it should be a \
+			 number, boolean, array, or inline table"
+		}
+		ErrorKind::ReusedKey => {
+			"rename one of the keys; if you meant to define several tables with the same \
+			 name, use an array of tables (`[[table]]`)"
+		}
+		ErrorKind::NumberTooLarge => {
+			"integers must fit in 64 bits; use a float or a string for larger numbers"
+		}
+		ErrorKind::NumberHasInvalidBaseOrLeadingZero => {
+			"remove the leading zeros; only integers can use the `0x`, `0o`, and `0b` \
+			 prefixes"
+		}
+		ErrorKind::InvalidNumber => return None,
+		ErrorKind::UnknownEscapeSequence => {
+			"valid escapes are \\b, \\t, \\n, \\f, \\r, \\\", \\\\, \\uXXXX, and \\UXXXXXXXX (plus \\e \
+			 and \\xXX in TOML 1.1); use `\\\\` for a literal backslash, or a literal string \
+			 ('...')"
+		}
+		ErrorKind::UnknownUnicodeScalar => {
+			"unicode escapes must be a valid scalar value - not a surrogate, and no larger \
+			 than 10FFFF"
+		}
+		ErrorKind::UnclosedBracket => "add a closing bracket",
+		ErrorKind::NoCommaDelimeter => "separate values with `,`",
+		ErrorKind::ControlCharacterInString => {
+			"use an escape instead, like `\\u0000`; literal strings ('...') can't contain \
+			 control characters at all"
+		}
+		ErrorKind::SignedDateTime => {
+			"remove the sign; to store a negative duration or offset, use a number or a string"
+		}
+		ErrorKind::NestingTooDeep => {
+			"flatten the document, or raise the limit with `ParseOptions::max_depth()` if it's \
+			 trusted"
+		}
+		ErrorKind::UnsupportedDateTime => "store the date or time as a string instead",
+		ErrorKind::ExpectedNewlineAfterValue => {
+			"put each key/value pair and table header on its own line; only a comment can \
+			 come after it"
+		}
+	})
+}
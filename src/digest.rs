@@ -0,0 +1,125 @@
+//! Summaries of TOML documents, so two copies of a document can be compared without
+//! sending the whole document around.
+
+use crate::{crate_prelude::*, Toml};
+
+/// Statistics about a valid TOML document, along with a digest of its content. Create one
+/// with [`validate_digest()`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ValidationReport {
+	/// The number of tables in the document, not counting the root table. This includes
+	/// inline tables, tables in arrays, and tables created by dotted keys.
+	pub sections: usize,
+	/// The number of keys in the document, in every table. Keys that name tables are
+	/// counted, too, so `a.b = 1` has 2 keys.
+	pub keys: usize,
+	/// How deeply the document's tables and arrays are nested. A document with only
+	/// top-level values has a depth of 1, and each table or array adds another level.
+	pub max_depth: usize,
+	/// A 64-bit FNV-1a hash of the document's logical content. Whitespace, comments, the
+	/// order of keys, and how values are written (like `0x10` vs `16`, or `'a'` vs `"a"`)
+	/// don't change the digest; only the keys and values themselves do.
+	///
+	/// Time values are currently unsupported, so only their type is hashed.
+	pub digest: u64,
+}
+
+/// Parses `source`, and if it's valid, returns statistics about it and a digest of its
+/// content. See [`ValidationReport`].
+///
+/// ```rust
+/// let first = boml::validate_digest("b = 2\na = 'x' # comment\n").unwrap();
+/// let second = boml::validate_digest("a = \"x\"\nb = 0x2\n").unwrap();
+/// assert_eq!(first.digest, second.digest);
+/// assert_eq!(first.keys, 2);
+/// ```
+pub fn validate_digest(source: &str) -> Result<ValidationReport, Error<'_>> {
+	let toml = Toml::parse(source)?;
+	let mut digest = Digest {
+		report: ValidationReport {
+			sections: 0,
+			keys: 0,
+			max_depth: 0,
+			digest: FNV_OFFSET_BASIS,
+		},
+	};
+	digest.table(&toml, 1);
+
+	Ok(digest.report)
+}
+
+const FNV_OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+const FNV_PRIME: u64 = 0x0000_0100_0000_01b3;
+
+/// Walks a document in canonical order - keys sorted by their bytes - and hashes a
+/// stream of tagged, length-prefixed values, so different documents can't produce the
+/// same stream.
+struct Digest {
+	report: ValidationReport,
+}
+impl Digest {
+	fn write(&mut self, bytes: &[u8]) {
+		for byte in bytes {
+			self.report.digest ^= *byte as u64;
+			self.report.digest = self.report.digest.wrapping_mul(FNV_PRIME);
+		}
+	}
+	fn write_len(&mut self, len: usize) {
+		self.write(&(len as u64).to_le_bytes());
+	}
+	fn write_str(&mut self, string: &str) {
+		self.write_len(string.len());
+		self.write(string.as_bytes());
+	}
+
+	fn table(&mut self, table: &Table<'_>, depth: usize) {
+		self.report.max_depth = self.report.max_depth.max(depth);
+
+		let mut entries: Vec<_> = table.iter().collect();
+		entries.sort_unstable_by_key(|(key, _)| *key);
+
+		self.write(b"{");
+		self.write_len(entries.len());
+		for (key, value) in entries {
+			self.report.keys += 1;
+			self.write_str(key);
+			self.value(value, depth);
+		}
+	}
+
+	fn value(&mut self, value: &TomlValue<'_>, depth: usize) {
+		match value {
+			TomlValue::String(string) => {
+				self.write(b"s");
+				self.write_str(string.as_str());
+			}
+			TomlValue::Integer(int) => {
+				self.write(b"i");
+				self.write(&int.to_le_bytes());
+			}
+			TomlValue::Float(float) => {
+				// Every NaN is the same value in TOML
+				let float = if float.is_nan() { f64::NAN } else { *float };
+				self.write(b"f");
+				self.write(&float.to_bits().to_le_bytes());
+			}
+			TomlValue::Boolean(bool_) => self.write(if *bool_ { b"t" } else { b"F" }),
+			TomlValue::OffsetDateTime => self.write(b"O"),
+			TomlValue::LocalDateTime => self.write(b"D"),
+			TomlValue::LocalDate => self.write(b"d"),
+			TomlValue::LocalTime => self.write(b"T"),
+			TomlValue::Array(array) => {
+				self.report.max_depth = self.report.max_depth.max(depth + 1);
+				self.write(b"[");
+				self.write_len(array.len());
+				for value in array {
+					self.value(value, depth + 1);
+				}
+			}
+			TomlValue::Table(table) => {
+				self.report.sections += 1;
+				self.table(table, depth + 1);
+			}
+		}
+	}
+}
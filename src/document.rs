@@ -0,0 +1,394 @@
+//! Defines [`TomlDocument`], for editing TOML without losing its formatting.
+
+use {
+	crate::{
+		crate_prelude::*,
+		parser,
+		serializer::{self, EscapeStyle},
+		text::BOM,
+		Toml,
+	},
+	std::{
+		fmt::{self, Display, Write},
+		ops::Range,
+	},
+};
+
+/// A TOML document that can be edited without destroying its formatting.
+///
+/// Edits are spliced directly into the document's source text, so comments, blank lines,
+/// key order, and the formatting of every value that isn't edited are kept exactly as
+/// they were. Use [`TomlDocument::toml()`] to read values from the document.
+///
+/// Only key/value pairs in `[table]` sections (or the root table) can be edited. Values
+/// inside inline tables or arrays of tables can't be targeted by a path, so editing them
+/// will fail.
+#[derive(Debug)]
+pub struct TomlDocument {
+	source: String,
+}
+impl TomlDocument {
+	/// Parses the provided string as TOML, and copies it into a new document.
+	pub fn parse(source: &str) -> Result<Self, Error<'_>> {
+		Toml::parse(source)?;
+
+		Ok(Self {
+			source: source.to_string(),
+		})
+	}
+
+	/// Parses the document, so values can be read from it.
+	pub fn toml(&self) -> Toml<'_> {
+		Toml::parse(&self.source).expect("TomlDocuments always contain valid TOML")
+	}
+
+	/// The document's source text, including any edits that have been made to it.
+	pub fn as_str(&self) -> &str {
+		&self.source
+	}
+
+	/// Consumes the document, returning its source text.
+	pub fn into_string(self) -> String {
+		self.source
+	}
+
+	/// Sets the value at `path`, which is a (possibly dotted) TOML key like `package.version`.
+	///
+	/// If the key already exists, only its value is replaced. Otherwise, a new key/value
+	/// pair is added to the end of the deepest `[table]` section the path points into, or
+	/// to the root table if there isn't one. New keys in an empty root table go after any
+	/// comments at the top of the document. New lines end with `\r\n` if the document's
+	/// lines do.
+	///
+	/// Each edit parses the whole document twice: once to find where the key is, and once
+	/// to check that the edited document is still valid. Making many edits to a large
+	/// document can be slow.
+	pub fn set(&mut self, path: &str, value: &TomlValue<'_>) -> Result<(), EditError> {
+		let path = parse_path(path)?;
+		let mut value_text = String::new();
+		serializer::write_value(&mut value_text, value, EscapeStyle::Minimal)
+			.map_err(|_| EditError::UnsupportedValue)?;
+
+		let layout = self.layout();
+		let mut edited = self.source.clone();
+
+		if let Some(entry) = layout.find_entry(&path) {
+			edited.replace_range(entry.value.clone(), &value_text);
+		} else {
+			let header = layout
+				.headers
+				.iter()
+				.enumerate()
+				.filter(|(_, header)| {
+					!header.array
+						&& header.path.len() < path.len()
+						&& path.starts_with(&header.path)
+				})
+				.max_by_key(|(_, header)| header.path.len());
+			let (header_idx, key) = match header {
+				Some((idx, header)) => (Some(idx), &path[header.path.len()..]),
+				None => (None, path.as_slice()),
+			};
+
+			// New keys in the root table go after the byte order mark, if there is one
+			let document_start = if edited.starts_with(BOM) {
+				BOM.len()
+			} else {
+				0
+			};
+			let insert_at = layout
+				.entries
+				.iter()
+				.rev()
+				.find(|entry| entry.header == header_idx)
+				.map(|entry| entry.line.end)
+				.or_else(|| header_idx.map(|idx| layout.headers[idx].line.end))
+				.unwrap_or_else(|| leading_comments_end(&edited, document_start));
+
+			let newline = line_ending(&edited);
+			let mut line = String::new();
+			if insert_at > document_start && !edited[..insert_at].ends_with('\n') {
+				line.push_str(newline);
+			}
+			write_dotted_key(&mut line, key).unwrap();
+			line.push_str(" = ");
+			line.push_str(&value_text);
+			line.push_str(newline);
+
+			edited.insert_str(insert_at, &line);
+		}
+
+		self.commit(edited, &path)
+	}
+
+	/// Removes the key/value pair at `path`, which is a (possibly dotted) TOML key like
+	/// `package.version`. The entire line the pair was on is removed, including any
+	/// comment at the end of the line.
+	pub fn remove(&mut self, path: &str) -> Result<(), EditError> {
+		let path = parse_path(path)?;
+		let layout = self.layout();
+		let Some(entry) = layout.find_entry(&path) else {
+			return Err(EditError::InvalidKey);
+		};
+
+		let mut edited = self.source.clone();
+		edited.replace_range(entry.line.clone(), "");
+
+		match Toml::parse(&edited) {
+			Ok(_) => {
+				self.source = edited;
+				Ok(())
+			}
+			Err(_) => Err(EditError::InvalidEdit),
+		}
+	}
+
+	/// Records the location of everything in the document.
+	fn layout(&self) -> Layout {
+		let mut layout = Layout::default();
+		Toml::parse_recording(&self.source, Some(&mut layout))
+			.expect("TomlDocuments always contain valid TOML");
+		layout
+	}
+
+	/// Replaces the document's source with `edited`, if it's still valid TOML and has a
+	/// value at `path`.
+	fn commit(&mut self, edited: String, path: &[String]) -> Result<(), EditError> {
+		let Ok(toml) = Toml::parse(&edited) else {
+			return Err(EditError::InvalidEdit);
+		};
+
+		let mut table: &Table<'_> = &toml;
+		for (idx, key) in path.iter().enumerate() {
+			let value = match table.get(key.as_str()) {
+				Some(TomlValue::Array(array)) if idx + 1 != path.len() => array.last(),
+				other => other,
+			};
+
+			match value {
+				_ if idx + 1 == path.len() && value.is_some() => {}
+				Some(TomlValue::Table(next)) => table = next,
+				_ => return Err(EditError::InvalidEdit),
+			}
+		}
+
+		self.source = edited;
+		Ok(())
+	}
+}
+impl Display for TomlDocument {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.write_str(&self.source)
+	}
+}
+
+/// Errors from editing a [`TomlDocument`].
+#[derive(Debug, PartialEq, Eq)]
+pub enum EditError {
+	/// The path couldn't be parsed as a TOML key.
+	InvalidPath,
+	/// There was no key/value pair at the provided path.
+	InvalidKey,
+	/// The value can't be written as TOML. Time values are currently unsupported.
+	UnsupportedValue,
+	/// Making the edit would have produced invalid TOML - for example, setting a key inside
+	/// a value that isn't a table.
+	InvalidEdit,
+}
+
+impl Display for EditError {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.write_str(match self {
+			Self::InvalidPath => "the path isn't a valid TOML key",
+			Self::InvalidKey => "there's no value at this path",
+			Self::UnsupportedValue => "the value can't be written as TOML",
+			Self::InvalidEdit => "the edit would produce invalid TOML",
+		})
+	}
+}
+impl std::error::Error for EditError {}
+
+/// The location of every table header and key/value pair in a document.
+#[derive(Debug, Default)]
+pub(crate) struct Layout {
+	headers: Vec<HeaderLayout>,
+	entries: Vec<EntryLayout>,
+}
+impl Layout {
+	/// Records a `[table]` or `[[table]]` header. `text.idx` should be right after the
+	/// header's closing bracket.
+	pub(crate) fn record_header(
+		&mut self,
+		text: &Text<'_>,
+		start: usize,
+		key: &Key<'_>,
+		array: bool,
+	) {
+		self.headers.push(HeaderLayout {
+			path: key_path(key),
+			array,
+			line: line_start(text.text, start)..line_end(text.text, text.idx),
+		});
+	}
+
+	/// Records a key/value pair. `text.idx` should be at the last byte of the value.
+	pub(crate) fn record_entry(&mut self, text: &Text<'_>, start: usize, key: &Key<'_>) {
+		let bytes = text.text.as_bytes();
+
+		let mut last_key = key;
+		while let Some(ref child) = last_key.child {
+			last_key = child;
+		}
+		let mut value_start = last_key.span().end + 1;
+		while bytes[value_start] != b'=' {
+			value_start += 1;
+		}
+		value_start += 1;
+		while bytes[value_start] == b' ' || bytes[value_start] == b'\t' {
+			value_start += 1;
+		}
+
+		let (header, mut path) = match self.headers.last() {
+			Some(header) => (Some(self.headers.len() - 1), header.path.clone()),
+			None => (None, Vec::new()),
+		};
+		path.extend(key_path(key));
+
+		self.entries.push(EntryLayout {
+			header,
+			path,
+			value: value_start..text.idx + 1,
+			line: line_start(text.text, start)..line_end(text.text, text.idx + 1),
+		});
+	}
+
+	/// Finds a key/value pair by its full path. Pairs in arrays of tables are ignored, since
+	/// a path can't say which element of the array it's referring to.
+	fn find_entry(&self, path: &[String]) -> Option<&EntryLayout> {
+		self.entries.iter().find(|entry| {
+			entry.path == path && !entry.header.is_some_and(|idx| self.headers[idx].array)
+		})
+	}
+}
+
+#[derive(Debug)]
+struct HeaderLayout {
+	/// The table name in the header.
+	path: Vec<String>,
+	/// If this is an array of tables header (`[[table]]`).
+	array: bool,
+	/// The byte range of the header's entire line, including its newline.
+	line: Range<usize>,
+}
+
+#[derive(Debug)]
+struct EntryLayout {
+	/// The index of the header for the section this pair is in, or `None` if it's in the
+	/// root table.
+	header: Option<usize>,
+	/// The full path to this pair's value - the header's path, followed by the pair's key.
+	path: Vec<String>,
+	/// The byte range of the value.
+	value: Range<usize>,
+	/// The byte range of the pair's entire line, including its newline.
+	line: Range<usize>,
+}
+
+/// Flattens a dotted key into each of its parts.
+fn key_path(key: &Key<'_>) -> Vec<String> {
+	let mut path = vec![key.text.as_str().to_string()];
+	let mut key = key;
+	while let Some(ref child) = key.child {
+		path.push(child.text.as_str().to_string());
+		key = child;
+	}
+	path
+}
+
+/// Parses a path like `package.version` into its parts.
+pub(crate) fn parse_path(path: &str) -> Result<Vec<String>, EditError> {
+	if path.trim().is_empty() {
+		return Err(EditError::InvalidPath);
+	}
+
+	// The key parser expects something to come after the key
+	let path = format!("{path}\n");
+	let mut text = Text {
+		text: &path,
+		..Default::default()
+	};
+	let key = parser::parse_key(&mut text).map_err(|_| EditError::InvalidPath)?;
+	text.idx += 1;
+	text.skip_whitespace();
+	if text.idx != path.len() - 1 {
+		return Err(EditError::InvalidPath);
+	}
+
+	Ok(key_path(&key))
+}
+
+/// Writes each part of a path, separated by dots.
+pub(crate) fn write_dotted_key<W: Write>(out: &mut W, path: &[String]) -> fmt::Result {
+	for (idx, key) in path.iter().enumerate() {
+		if idx != 0 {
+			out.write_char('.')?;
+		}
+		serializer::write_key(out, key)?;
+	}
+	Ok(())
+}
+
+/// Finds the end of the comments at the start of the document, which starts at `idx`.
+/// Blank lines between the comments are skipped. The returned index is just after the
+/// last comment's newline, or `idx` if the document doesn't start with a comment.
+fn leading_comments_end(source: &str, mut idx: usize) -> usize {
+	let mut end = idx;
+	for line in source[idx..].split_inclusive('\n') {
+		let trimmed = line.trim_start_matches([' ', '\t']);
+		if trimmed.starts_with('#') {
+			end = idx + line.len();
+		} else if !trimmed.trim_end().is_empty() {
+			break;
+		}
+		idx += line.len();
+	}
+	end
+}
+
+/// The line ending the document uses, going by its first line.
+fn line_ending(source: &str) -> &'static str {
+	match source.find('\n') {
+		Some(idx) if source[..idx].ends_with('\r') => "\r\n",
+		_ => "\n",
+	}
+}
+
+/// Finds the start of the line `idx` is on, if there's only whitespace before it.
+fn line_start(source: &str, mut idx: usize) -> usize {
+	let bytes = source.as_bytes();
+	while idx > 0 && (bytes[idx - 1] == b' ' || bytes[idx - 1] == b'\t') {
+		idx -= 1;
+	}
+	idx
+}
+
+/// Finds the end of the line `idx` is on, skipping trailing whitespace and comments. The
+/// returned index is just after the line's newline, or the end of the source.
+fn line_end(source: &str, mut idx: usize) -> usize {
+	let bytes = source.as_bytes();
+	while idx < bytes.len() && (bytes[idx] == b' ' || bytes[idx] == b'\t') {
+		idx += 1;
+	}
+	if bytes.get(idx) == Some(&b'#') {
+		while idx < bytes.len() && bytes[idx] != b'\n' {
+			idx += 1;
+		}
+	}
+	if bytes.get(idx) == Some(&b'\r') && bytes.get(idx + 1) == Some(&b'\n') {
+		idx += 1;
+	}
+	if bytes.get(idx) == Some(&b'\n') {
+		idx += 1;
+	}
+	idx
+}
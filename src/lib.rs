@@ -1,119 +1,318 @@
+pub mod comments;
+#[cfg(feature = "miette")]
+pub mod diagnostic;
+pub mod digest;
+pub mod document;
+pub mod lint;
+pub mod options;
+pub mod owned;
 pub mod parser;
+#[cfg(feature = "serde")]
+pub mod serde;
+pub mod serializer;
 pub mod table;
 pub mod text;
+#[cfg(feature = "toml-compat")]
+pub mod toml_compat;
 pub mod types;
 
-use {crate_prelude::*, std::ops::Deref};
+pub use digest::validate_digest;
+
+/// Parses `text` as TOML with the provided [`ParseOptions`]. This is the same as
+/// [`Toml::parse_with()`].
+///
+/// [`ParseOptions`]: options::ParseOptions
+#[inline(always)]
+pub fn parse_with<'a>(text: &'a str, options: &ParseOptions) -> Result<Toml<'a>, Error<'a>> {
+	Toml::parse_with(text, options)
+}
+
+use {
+	comments::{Comment, CommentCapture},
+	crate_prelude::*,
+	document::Layout,
+	options::ParseOptions,
+	std::{
+		fmt::{self, Display},
+		ops::Deref,
+	},
+	table::Definition,
+	text::is_bare_key_byte,
+};
 
 /// BOML's TOML parser. Create a new one with [`new()`] or [`parse()`], then use
 /// it just like a [`Table`].
 ///
 /// [`new()`]: Toml::new()
 /// [`parse()`]: Toml::parse()
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Toml<'a> {
 	table: Table<'a>,
+	source: &'a str,
+	comments: Vec<Comment<'a>>,
 }
 impl<'a> Toml<'a> {
 	/// A wrapper around [`Toml::parse()`].
 	#[inline(always)]
-	pub fn new(text: &'a str) -> Result<Self, Error> {
+	pub fn new(text: &'a str) -> Result<Self, Error<'a>> {
 		Self::parse(text)
 	}
 
 	/// Attempts to parse the provided string as TOML.
-	pub fn parse(text: &'a str) -> Result<Self, Error> {
-		let mut text = Text { text, idx: 0 };
-		text.skip_whitespace_and_newlines();
-		let mut root_table = Table::default();
-		// (table name, table, if it's a member of an array of tables)
-		let mut current_table: Option<(Key<'_>, Table<'_>, bool)> = None;
-
-		while text.idx < text.end() {
-			match text.current_byte().unwrap() {
-				// Comment
-				b'#' => {
-					if let Some(newline_idx) = text.excerpt(text.idx..).find(b'\n') {
-						text.idx = newline_idx;
-					} else {
-						// Comment is at end of file
-						break;
-					}
-				}
-				// Table definition
-				b'[' => {
-					if let Some((key, table, array)) = current_table.take() {
-						insert_subtable(&mut root_table, key, table, array)?;
-					}
+	#[inline(always)]
+	pub fn parse(text: &'a str) -> Result<Self, Error<'a>> {
+		Self::parse_recording(text, None)
+	}
 
-					if text.byte(text.idx + 1) == Some(b'[') {
-						text.idx += 2;
-						text.skip_whitespace();
-						let table_name = parser::parse_key(&mut text)?;
-						text.idx += 1;
-						text.skip_whitespace();
+	/// Attempts to parse the provided string as TOML, with the provided [`ParseOptions`].
+	/// [`Toml::parse()`] is the same as parsing with the default options.
+	#[inline(always)]
+	pub fn parse_with(text: &'a str, options: &ParseOptions) -> Result<Self, Error<'a>> {
+		Self::parse_inner(text, *options, None, None)
+	}
 
-						if text.current_byte() != Some(b']')
-							|| text.byte(text.idx + 1) != Some(b']')
-						{
-							return Err(Error {
-								start: table_name.text.span().start - 1,
-								end: table_name.text.span().end,
-								kind: ErrorKind::UnclosedBracket,
-							});
-						}
-						text.idx += 2;
-
-						current_table = Some((table_name, Table::default(), true));
-					} else {
-						text.idx += 1;
-						text.skip_whitespace();
-						let table_name = parser::parse_key(&mut text)?;
-						text.idx += 1;
-						text.skip_whitespace();
-
-						if text.current_byte() != Some(b']') {
-							return Err(Error {
-								start: table_name.text.span().start - 1,
-								end: table_name.text.span().end,
-								kind: ErrorKind::UnclosedBracket,
-							});
-						}
-						text.idx += 1;
+	/// Parses the provided string as TOML, but instead of stopping at the first error,
+	/// skips past it and keeps parsing to find every error in the document.
+	///
+	/// After an error, parsing resumes at the next line that isn't part of the broken
+	/// key/value pair or table header. Keys under a broken table header are checked for
+	/// errors, but otherwise ignored.
+	pub fn parse_all(text: &'a str) -> Result<Self, Vec<Error<'a>>> {
+		Self::parse_all_with(text, &ParseOptions::default())
+	}
 
-						current_table = Some((table_name, Table::default(), false));
-					}
+	/// Identical to [`Toml::parse_all()`], but with the provided [`ParseOptions`].
+	pub fn parse_all_with(text: &'a str, options: &ParseOptions) -> Result<Self, Vec<Error<'a>>> {
+		let (toml, errors) = Self::parse_lenient_with(text, options);
+
+		if errors.is_empty() {
+			Ok(toml)
+		} else {
+			Err(errors)
+		}
+	}
+
+	/// Parses as much of the provided string as possible, returning everything that
+	/// parsed successfully along with every error that was found. This is useful for
+	/// tools like editors, which need to work with documents that are still being
+	/// written.
+	///
+	/// Errors are recovered from the same way as in [`Toml::parse_all()`]: broken
+	/// key/value pairs are skipped, and so are tables with broken headers. Nothing is
+	/// inserted for the broken parts of the document, but everything else is kept.
+	pub fn parse_lenient(text: &'a str) -> (Self, Vec<Error<'a>>) {
+		Self::parse_lenient_with(text, &ParseOptions::default())
+	}
+
+	/// Identical to [`Toml::parse_lenient()`], but with the provided [`ParseOptions`].
+	pub fn parse_lenient_with(text: &'a str, options: &ParseOptions) -> (Self, Vec<Error<'a>>) {
+		let mut errors = Vec::new();
+		let toml = Self::parse_inner(text, *options, None, Some(&mut errors))
+			.expect("Parsing with error recovery never fails");
+
+		(toml, errors)
+	}
+
+	/// Parses TOML and records the location of every table header and key/value pair in
+	/// `layout`, for use in [`TomlDocument`]s.
+	///
+	/// [`TomlDocument`]: document::TomlDocument
+	#[inline(always)]
+	pub(crate) fn parse_recording(
+		text: &'a str,
+		layout: Option<&mut Layout>,
+	) -> Result<Self, Error<'a>> {
+		Self::parse_inner(text, ParseOptions::default(), layout, None)
+	}
+
+	/// The actual TOML parser. If `layout` is provided, the location of every table header
+	/// and key/value pair gets recorded in it. If `errors` is provided, errors are pushed
+	/// to it and parsing recovers from them, instead of stopping at the first error.
+	fn parse_inner(
+		text: &'a str,
+		options: ParseOptions,
+		mut layout: Option<&mut Layout>,
+		mut errors: Option<&mut Vec<Error<'a>>>,
+	) -> Result<Self, Error<'a>> {
+		let mut text = Text {
+			text,
+			options,
+			..Default::default()
+		};
+		text.skip_bom();
+		text.skip_whitespace_and_newlines();
+		let mut root_table = Table::with_hasher(0, options.table_hasher());
+		let mut current_table = None;
+		let mut capture = options.capture_comments.then(CommentCapture::default);
+
+		while text.idx < text.text.len() {
+			let item_start = text.idx;
+			if let Some(ref mut capture) = capture {
+				if text.current_byte() == Some(b'#') {
+					capture.comment(&text);
 				}
-				// Key definition
-				_ => {
-					let (key, value) = parser::parse_assignment(&mut text)?;
+			}
 
-					let table = if let Some((_, ref mut table, _)) = current_table {
-						table
-					} else {
-						&mut root_table
-					};
+			let result = parse_item(&mut text, &mut root_table, &mut current_table, &mut layout);
+			if let Some(ref mut capture) = capture {
+				match result {
+					Ok(Some(item)) => capture.item(item, text.idx),
+					Ok(None) => {}
+					Err(_) => capture.error(),
+				}
+			}
+			if let Err(mut err) = result {
+				err.section = err.section.or(current_table
+					.as_ref()
+					.map(|current: &CurrentTable<'_>| current.header));
 
-					table.insert(key, value);
+				let Some(ref mut errors) = errors else {
+					return Err(err);
+				};
+				text.idx = recovery_point(&text, item_start, err.src.end);
+				errors.push(err);
 
-					text.idx += 1;
+				// If the header itself was broken, the keys after it have nowhere to go
+				let is_header = text.byte(item_start) == Some(b'[');
+				if is_header
+					&& current_table
+						.as_ref()
+						.map_or(true, |current| current.header.start != item_start)
+				{
+					if let Some(previous) = current_table.take() {
+						if let Err(err) = insert_subtable(&mut root_table, previous) {
+							errors.push(err);
+						}
+					}
+					let header = text.excerpt(item_start..text.idx);
+					current_table = Some(CurrentTable {
+						name: Key {
+							text: CowSpan::Raw(header),
+							child: None,
+						},
+						table: Table::with_hasher(0, options.table_hasher()),
+						array: false,
+						depth: 0,
+						header,
+						target: Vec::new(),
+						discard: true,
+					});
 				}
 			}
 
 			text.skip_whitespace_and_newlines();
 		}
 
-		if let Some((key, table, array)) = current_table.take() {
-			insert_subtable(&mut root_table, key, table, array)?;
+		if let Some(current) = current_table.take() {
+			if let Err(err) = insert_subtable(&mut root_table, current) {
+				match errors {
+					Some(errors) => errors.push(err),
+					None => return Err(err),
+				}
+			}
 		}
 
-		Ok(Self { table: root_table })
+		Ok(Self {
+			table: root_table,
+			source: text.text,
+			comments: capture.map(|capture| capture.comments).unwrap_or_default(),
+		})
+	}
+
+	/// Every comment in the document, in order. Comments are only captured when parsing
+	/// with [`ParseOptions::capture_comments()`], so this is empty otherwise.
+	///
+	/// Each comment belongs to the item after it, or to the item on the same line if it's
+	/// at the end of a line. Comments inside multi-line arrays and inline tables aren't
+	/// captured.
+	///
+	/// ```
+	/// use boml::{comments::CommentPlacement, prelude::*};
+	///
+	/// let source = "# The port to listen on\nport = 8080 # Must be above 1024\n";
+	/// let toml = Toml::parse_with(source, &ParseOptions::new().capture_comments(true)).unwrap();
+	///
+	/// let comments = toml.comments();
+	/// assert_eq!(comments[0].text(), "The port to listen on");
+	/// assert_eq!(comments[0].placement, CommentPlacement::Leading);
+	/// assert_eq!(comments[1].text(), "Must be above 1024");
+	/// assert_eq!(comments[1].placement, CommentPlacement::Trailing);
+	/// assert_eq!(comments[1].item.unwrap().as_str(), "port");
+	/// ```
+	#[inline]
+	pub fn comments(&self) -> &[Comment<'a>] {
+		&self.comments
+	}
+
+	/// The comments that belong to the item at `span`, which can be a table's header, or
+	/// a key from [`Table::key_span()`].
+	pub fn comments_for<'s>(&'s self, span: Span<'_>) -> impl Iterator<Item = &'s Comment<'a>> {
+		self.comments.iter().filter(move |comment| {
+			comment
+				.item
+				.is_some_and(|item| item.start <= span.start && span.start <= item.end)
+		})
 	}
 
 	/// Consumes the [`Toml<'_>`], producing a [`Table<'_>`].
 	pub fn into_table(self) -> Table<'a> {
 		self.table
 	}
+
+	/// The text this TOML was parsed from.
+	#[inline]
+	pub fn source(&self) -> &'a str {
+		self.source
+	}
+
+	/// Consumes the [`Toml<'_>`], returning the text it was parsed from. Since the
+	/// returned string isn't borrowed from the [`Toml<'_>`], it can be kept around after
+	/// the values that were needed have been extracted:
+	///
+	/// ```
+	/// use boml::prelude::*;
+	///
+	/// // Only owned data, so it doesn't borrow from the source or the `Toml`
+	/// struct Package {
+	///     name: String,
+	///     version: String,
+	///     edition: Option<i64>,
+	/// }
+	///
+	/// fn load(source: &str) -> Result<(Package, &str), OwnedTomlError> {
+	///     let toml = Toml::parse(source)?;
+	///     let package = toml.get_table("package").unwrap();
+	///     let package = Package {
+	///         name: package.get_string("name").unwrap().to_string(),
+	///         version: package.get_string("version").unwrap().to_string(),
+	///         edition: package.get_integer("edition").ok(),
+	///     };
+	///
+	///     // The `Toml` is dropped here, but the source can still be returned
+	///     Ok((package, toml.into_source()))
+	/// }
+	///
+	/// let (package, source) = load("[package]\nname = 'boml'\nversion = '0.3.1'\n").unwrap();
+	/// assert_eq!(package.name, "boml");
+	/// assert_eq!(package.edition, None);
+	/// assert!(source.starts_with("[package]"));
+	/// ```
+	#[inline]
+	pub fn into_source(self) -> &'a str {
+		self.source
+	}
+
+	/// Consumes the [`Toml<'_>`], returning both its root table and the text it was
+	/// parsed from.
+	#[inline]
+	pub fn into_parts(self) -> (Table<'a>, &'a str) {
+		(self.table, self.source)
+	}
+}
+impl<'a> From<Toml<'a>> for Table<'a> {
+	fn from(toml: Toml<'a>) -> Self {
+		toml.into_table()
+	}
 }
 impl<'a> Deref for Toml<'a> {
 	type Target = Table<'a>;
@@ -123,69 +322,661 @@ impl<'a> Deref for Toml<'a> {
 	}
 }
 
-fn insert_subtable<'a>(
-	root_table: &mut Table<'a>,
-	key: Key<'a>,
+/// The table currently being parsed, after a `[table]` or `[[table]]` header.
+struct CurrentTable<'a> {
+	/// The table's name.
+	name: Key<'a>,
+	/// The keys and values in the table.
 	table: Table<'a>,
+	/// If the table is a member of an array of tables.
 	array: bool,
-) -> Result<(), Error> {
-	let (start, end) = (key.text.span().start, key.text.span().end);
+	/// How deeply nested the table is, which is where nesting starts for its keys. See
+	/// [`Text::nest()`].
+	depth: usize,
+	/// The table's entire header, including brackets.
+	header: Span<'a>,
+	/// Which element to use for each array of tables in the table's name. See
+	/// [`resolve_header()`].
+	target: Vec<Option<usize>>,
+	/// If the table's header was invalid, so the table should be thrown away instead of
+	/// inserted into the document. Only used when recovering from errors.
+	discard: bool,
+}
 
-	if array {
-		let Some(TomlValue::Array(array)) =
-			root_table.get_or_insert_mut(key, TomlValue::Array(Vec::new()))
-		else {
-			return Err(Error {
-				start,
-				end,
-				kind: ErrorKind::ReusedKey,
+/// Parses the next item in the document - a comment, table header, or key/value pair.
+/// Returns the span of the table header, or the key of the key/value pair, if the item
+/// wasn't a comment.
+fn parse_item<'a>(
+	text: &mut Text<'a>,
+	root_table: &mut Table<'a>,
+	current_table: &mut Option<CurrentTable<'a>>,
+	layout: &mut Option<&mut Layout>,
+) -> Result<Option<Span<'a>>, Error<'a>> {
+	let item = match text.current_byte().unwrap() {
+		// Comment
+		b'#' => {
+			// If there's no newline, the comment is at the end of the file
+			text.idx = text
+				.excerpt(text.idx..)
+				.find(b'\n')
+				.unwrap_or(text.text.len());
+			None
+		}
+		// Table definition
+		b'[' => {
+			let header_start = text.idx;
+			let array = text.byte(text.idx + 1) == Some(b'[');
+
+			text.idx += if array { 2 } else { 1 };
+			text.skip_whitespace();
+			if text.current_byte().is_none() {
+				return Err(Error {
+					src: text.excerpt(header_start..),
+					kind: ErrorKind::UnclosedBracket,
+					section: None,
+				});
+			}
+			text.depth = 0;
+			let name = parser::parse_key(text)?;
+			if array {
+				// Tables in an array of tables are nested in the array
+				text.nest(header_start)?;
+			}
+			text.idx += 1;
+			text.skip_whitespace();
+
+			let closed = if array {
+				text.current_byte() == Some(b']') && text.byte(text.idx + 1) == Some(b']')
+			} else {
+				text.current_byte() == Some(b']')
+			};
+			if !closed {
+				return Err(Error {
+					src: text.excerpt(header_start..=name.span().end),
+					kind: ErrorKind::UnclosedBracket,
+					section: None,
+				});
+			}
+			text.idx += if array { 2 } else { 1 };
+
+			if let Some(layout) = layout {
+				layout.record_header(text, header_start, &name, array);
+			}
+
+			// The previous table has to be inserted before this header is resolved, since
+			// it may have added to an array of tables this header refers to
+			let previous = current_table
+				.take()
+				.map(|previous| insert_subtable(root_table, previous));
+			let header = text.excerpt(header_start..text.idx);
+			let target = resolve_header(root_table, &name, array, header);
+			let (path, capacity) = target.as_ref().cloned().unwrap_or_default();
+			*current_table = Some(CurrentTable {
+				name,
+				table: Table::with_hasher(capacity, text.options.table_hasher()),
+				array,
+				depth: text.depth,
+				header,
+				discard: target.is_err(),
+				target: path,
 			});
+
+			if let Some(previous) = previous {
+				previous?;
+			}
+			target?;
+			parser::expect_line_end(text, text.idx)?;
+			Some(header)
+		}
+		// Key definition
+		_ => {
+			let assignment_start = text.idx;
+			text.depth = current_table.as_ref().map_or(0, |current| current.depth);
+			let (key, value) = parser::parse_assignment(text)?;
+
+			if let Some(layout) = layout {
+				layout.record_entry(text, assignment_start, &key);
+			}
+
+			// The whole key, including the closing quote if the last part is quoted
+			let mut last_key = &key;
+			while let Some(ref child) = last_key.child {
+				last_key = child;
+			}
+			let mut key_end = last_key.span().end;
+			if matches!(text.byte(key_end + 1), Some(b'"' | b'\'')) {
+				key_end += 1;
+			}
+			let key_span = text.excerpt(assignment_start..=key_end);
+
+			let table = match current_table {
+				Some(ref mut current) => &mut current.table,
+				None => root_table,
+			};
+			table.insert(key, value)?;
+			parser::expect_line_end(text, text.idx + 1)?;
+
+			text.idx += 1;
+			Some(key_span)
+		}
+	};
+
+	Ok(item)
+}
+
+/// Finds the table a `[table]` or `[[table]]` header refers to, and checks that the header
+/// doesn't conflict with values that are already in the document.
+///
+/// Each part of a header's name is a key in the table before it, and may refer to:
+/// - Nothing, in which case a table will be created for it.
+/// - A table, in which case that table is used.
+/// - An array of tables (from `[[table]]` headers), in which case the *last* table in
+///   the array is used. Which element is last can change as more `[[table]]` headers are
+///   parsed, so it's resolved right here, when the header is parsed, and stored in the
+///   returned list (one entry for each part of the name, except the last). For example,
+///   in `[[a]] [[a.b]] [[a]] [a.b]`, the first `[[a.b]]` is in `a[0]` and the `[a.b]` is
+///   in `a[1]`.
+///
+/// Inline tables (including the tables in static arrays) can't be extended, so no part of
+/// the name can refer to one. The last part of the name has to refer to nothing or a
+/// table that hasn't been defined yet for `[table]` headers - tables that were already
+/// defined by a header or dotted keys can't be defined again - or nothing or an array of
+/// tables for `[[table]]` headers. Anything else - like a header for a key that was
+/// already defined as a string - is a [`ErrorKind::ReusedKey`] error.
+///
+/// Along with the path, this returns how many keys the new table is likely to have, so it
+/// can be allocated up front. Tables in an array of tables usually have the same keys, so
+/// that's the size of the last table in the array, if there is one.
+fn resolve_header<'a>(
+	root_table: &Table<'a>,
+	name: &Key<'a>,
+	array: bool,
+	header: Span<'a>,
+) -> Result<(Vec<Option<usize>>, usize), Error<'a>> {
+	let mut target = Vec::new();
+	// `None` once the path reaches a table that doesn't exist yet
+	let mut table = Some(root_table);
+	let mut key = name;
+
+	loop {
+		let value = table.and_then(|table| table.get(key.text.as_str()));
+		let Some(ref child) = key.child else {
+			let valid = match value {
+				None => true,
+				Some(TomlValue::Table(table)) => !array && table.definition == Definition::Implicit,
+				Some(TomlValue::Array(tables)) => {
+					array
+						&& !tables.is_empty()
+						&& tables.iter().all(
+							|value| matches!(value, TomlValue::Table(table) if table.definition == Definition::Header),
+						)
+				}
+				Some(_) => false,
+			};
+			if !valid {
+				return Err(Error {
+					src: key.span(),
+					kind: ErrorKind::ReusedKey,
+					section: Some(header),
+				});
+			}
+
+			let capacity = match value {
+				Some(TomlValue::Array(tables)) => tables
+					.last()
+					.and_then(TomlValue::table)
+					.map_or(0, |table| table.len()),
+				_ => 0,
+			};
+			return Ok((target, capacity));
+		};
+
+		table = match value {
+			None => {
+				target.push(None);
+				None
+			}
+			Some(TomlValue::Table(next)) if next.definition != Definition::Inline => {
+				target.push(None);
+				Some(next)
+			}
+			Some(TomlValue::Array(tables))
+				if matches!(
+					tables.last(),
+					Some(TomlValue::Table(table)) if table.definition == Definition::Header
+				) =>
+			{
+				target.push(Some(tables.len() - 1));
+				tables.last().and_then(TomlValue::table)
+			}
+			Some(_) => {
+				return Err(Error {
+					src: key.span(),
+					kind: ErrorKind::ReusedKey,
+					section: Some(header),
+				})
+			}
+		};
+		key = child;
+	}
+}
+
+/// Inserts a table into the document, at the location found by [`resolve_header()`].
+fn insert_subtable<'a>(
+	root_table: &mut Table<'a>,
+	current: CurrentTable<'a>,
+) -> Result<(), Error<'a>> {
+	let CurrentTable {
+		name,
+		mut table,
+		array,
+		header,
+		target,
+		discard,
+		..
+	} = current;
+	if discard {
+		return Ok(());
+	}
+
+	let reused_key = |key: &Key<'a>| Error {
+		src: key.span(),
+		kind: ErrorKind::ReusedKey,
+		section: Some(header),
+	};
+	let mut parent = root_table;
+	let mut key = name;
+	let mut target = target.into_iter();
+
+	while let Some(child) = key.child.take() {
+		let err = reused_key(&key);
+		let hasher = parent.map.hasher().clone();
+		let value = parent
+			.map
+			.entry(key.text)
+			.or_insert_with(|| TomlValue::Table(Table::with_hasher(0, hasher)));
+
+		parent = match (value, target.next().flatten()) {
+			(TomlValue::Table(table), None) => table,
+			(TomlValue::Array(tables), Some(idx)) => match tables.get_mut(idx) {
+				Some(TomlValue::Table(table)) => table,
+				_ => return Err(err),
+			},
+			_ => return Err(err),
 		};
-		array.push(TomlValue::Table(table));
-	} else {
-		let Some(TomlValue::Table(to_insert)) =
-			root_table.get_or_insert_mut(key, TomlValue::Table(Table::default()))
+		key = *child;
+	}
+
+	let err = reused_key(&key);
+	table.definition = Definition::Header;
+	table.header = Some(header);
+	if array {
+		let TomlValue::Array(tables) = parent
+			.map
+			.entry(key.text)
+			.or_insert_with(|| TomlValue::Array(Vec::new()))
 		else {
-			return Err(Error {
-				start,
-				end,
+			return Err(err);
+		};
+		tables.push(TomlValue::Table(table));
+
+		return Ok(());
+	}
+
+	let hasher = parent.map.hasher().clone();
+	let TomlValue::Table(to_insert) = parent
+		.map
+		.entry(key.text)
+		.or_insert_with(|| TomlValue::Table(Table::with_hasher(0, hasher)))
+	else {
+		return Err(err);
+	};
+	to_insert.definition = Definition::Header;
+	to_insert.header.get_or_insert(header);
+
+	// Keys that were already defined keep their first value, and the rest of the table
+	// is still inserted, so nothing is lost when recovering from the error
+	let mut result = Ok(());
+	for (key, value) in table.map {
+		if !to_insert.map.contains_key(key.as_str()) {
+			to_insert.map.insert(key, value);
+		} else if result.is_ok() {
+			result = Err(Error {
+				src: key.source_span().copied().unwrap_or(header),
 				kind: ErrorKind::ReusedKey,
+				section: Some(header),
 			});
-		};
+		}
+	}
 
-		for (key, value) in table.map {
-			let (start, end) = (key.span().start, key.span().end);
-			let old = to_insert.map.insert(key, value);
+	result
+}
 
-			if old.is_some() {
-				return Err(Error {
-					start,
-					end,
-					kind: ErrorKind::ReusedKey,
-				});
+/// Finds where parsing should resume after an error, for [`Toml::parse_all()`]. This is
+/// the start of the first line after the error that isn't part of the broken item (the
+/// table header or key/value pair that starts at `item_start`). Brackets and strings are
+/// tracked, so a broken array or inline table is skipped as a whole instead of causing
+/// more errors. The returned index is always after `item_start`.
+fn recovery_point(text: &Text<'_>, item_start: usize, error_end: usize) -> usize {
+	let bytes = text.text.as_bytes();
+	let is_header = bytes[item_start] == b'[';
+	let mut open_brackets = Vec::new();
+	let mut idx = item_start;
+
+	while idx < bytes.len() {
+		match bytes[idx] {
+			// Arrays can span multiple lines, but headers and inline tables can't
+			b'\n' if idx >= error_end && (is_header || !open_brackets.contains(&b'[')) => {
+				return idx + 1;
+			}
+			b'#' => {
+				while idx + 1 < bytes.len() && bytes[idx + 1] != b'\n' {
+					idx += 1;
+				}
+			}
+			b'[' | b'{' if !is_header => open_brackets.push(bytes[idx]),
+			b']' | b'}' if !is_header => {
+				open_brackets.pop();
+			}
+			quote @ (b'"' | b'\'') if !is_header => {
+				let multiline =
+					bytes[idx..].starts_with(if quote == b'"' { b"\"\"\"" } else { b"'''" });
+				idx += if multiline { 3 } else { 1 };
+
+				while idx < bytes.len() {
+					match bytes[idx] {
+						b'\\' if quote == b'"' => idx += 1,
+						b'\n' if !multiline => break,
+						byte if byte == quote
+							&& (!multiline || bytes[idx..].starts_with(&[quote; 3])) =>
+						{
+							if multiline {
+								idx += 2;
+							}
+							break;
+						}
+						_ => {}
+					}
+					idx += 1;
+				}
+				// Don't skip the newline that ended a single-line string
+				if bytes.get(idx) == Some(&b'\n') {
+					continue;
+				}
 			}
+			_ => {}
 		}
+
+		idx += 1;
 	}
 
-	Ok(())
+	bytes.len()
 }
 
 /// An error while parsing TOML, and the range of text that caused
 /// that error.
 #[derive(Debug)]
-pub struct Error {
-	/// The first byte (inclusive) of the text that caused a parsing
-	/// error.
-	pub start: usize,
-	/// The last byte (inclusive) of the text that caused a parsing
-	/// error.
-	pub end: usize,
+pub struct Error<'a> {
+	/// The text that caused the parsing error.
+	pub src: Span<'a>,
 	/// The type of parsing error; see the [`ErrorKind`] docs.
 	pub kind: ErrorKind,
+	/// The header (ie `[table]`) of the table that was being parsed when the error
+	/// occurred. This is `None` if the error occurred in the root table.
+	pub section: Option<Span<'a>>,
 }
+impl<'a> Error<'a> {
+	/// Checks if this error was caused by the document ending in the middle of something,
+	/// like an unclosed string or bracket, or a key without a value. This usually means
+	/// the document was truncated - for example, by an interrupted write.
+	pub fn is_truncation(&self) -> bool {
+		matches!(
+			self.kind,
+			ErrorKind::UnclosedString
+				| ErrorKind::UnclosedBracket
+				| ErrorKind::NoEqualsInAssignment
+				| ErrorKind::NoValueInAssignment
+		) && self.src.end + 1 >= self.src.source.trim_end().len()
+	}
+
+	/// If this error is an unrecognised value that looks like a bare word, like `yes` or
+	/// `12ab`, returns that word. It was probably meant to be a string, but wasn't quoted.
+	pub(crate) fn unquoted_string(&self) -> Option<&'a str> {
+		if self.kind != ErrorKind::UnrecognisedValue {
+			return None;
+		}
+
+		let word = self.src.clamped_str();
+		let unsigned = word.trim_start_matches(['+', '-']);
+		let is_bare = word.bytes().all(is_bare_key_byte);
+		let has_letter = word.bytes().any(|byte| byte.is_ascii_alphabetic());
+		// Typos in numbers with a base, like `0x1g`, aren't strings
+		let has_base = ["0x", "0o", "0b"]
+			.iter()
+			.any(|base| unsigned.starts_with(base));
+
+		(is_bare && has_letter && !has_base).then_some(word)
+	}
 
-/// A type of error while parsing TOML.
-#[derive(Debug, PartialEq, Eq)]
+	/// The line and column the error starts at. See [`Span::line_col()`].
+	#[inline]
+	pub fn line_col(&self) -> (usize, usize) {
+		self.src.line_col()
+	}
+
+	/// The full text of the line the error starts on. See [`Span::line_text()`].
+	#[inline]
+	pub fn line_text(&self) -> &'a str {
+		self.src.line_text()
+	}
+
+	/// Associates this error with the name of the file it came from. The name is shown
+	/// when the error is displayed, and is used as the source's name in diagnostics.
+	pub fn with_file_name(self, name: impl Into<String>) -> NamedError<'a> {
+		NamedError {
+			name: name.into(),
+			error: self,
+		}
+	}
+
+	/// Copies everything needed to display this error into an [`OwnedError`], which
+	/// doesn't borrow the TOML source. This lets the error be returned after the source
+	/// is dropped, or be boxed as a `dyn Error + 'static`.
+	///
+	/// ```
+	/// use boml::prelude::*;
+	///
+	/// fn load_port(source: String) -> anyhow::Result<i64> {
+	///     let toml = Toml::parse(&source).map_err(|err| err.to_owned_error())?;
+	///     let port = toml
+	///         .get_integer("port")
+	///         .map_err(|err| anyhow::anyhow!("`port`: {err}"))?;
+	///     Ok(port)
+	/// }
+	///
+	/// assert_eq!(load_port("port = 80".to_string()).unwrap(), 80);
+	///
+	/// let err = load_port("port = ".to_string()).unwrap_err();
+	/// assert!(err.to_string().contains("expected a value after `=`"));
+	/// let err = load_port("port = true".to_string()).unwrap_err();
+	/// assert!(err.to_string().contains("`port`: "));
+	/// ```
+	pub fn to_owned_error(&self) -> OwnedError {
+		let line_col = self.line_col();
+		let excerpt = self.src.clamped_str();
+		let excerpt = excerpt
+			.split_once('\n')
+			.map_or(excerpt, |(first_line, _)| first_line)
+			.trim_end_matches('\r');
+		let source = self.src.source;
+		let context = source
+			.strip_prefix(text::BOM)
+			.unwrap_or(source)
+			.lines()
+			.skip(line_col.0.saturating_sub(2))
+			.take(if line_col.0 == 1 { 2 } else { 3 })
+			.collect::<Vec<_>>()
+			.join("\n");
+
+		OwnedError {
+			kind: self.kind,
+			start: self.src.start,
+			end: self.src.end,
+			line_col,
+			excerpt: excerpt.to_string(),
+			context,
+			section: self
+				.section
+				.map(|section| section.clamped_str().to_string()),
+			message: self.to_string(),
+		}
+	}
+
+	/// Identical to [`Error::to_owned_error()`], but it consumes `self`.
+	#[inline]
+	pub fn into_owned(self) -> OwnedError {
+		self.to_owned_error()
+	}
+}
+impl Display for Error<'_> {
+	/// Renders the error like rustc does - the error message, followed by the line the
+	/// error is on, with the error's span underlined.
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		self.render(f, None)
+	}
+}
+impl Error<'_> {
+	/// Renders the error for its [`Display`] implementation, optionally with the name of
+	/// the file it came from.
+	fn render(&self, f: &mut fmt::Formatter<'_>, file_name: Option<&str>) -> fmt::Result {
+		let (line, col) = self.line_col();
+		let line_text = self.line_text();
+		let gutter = " ".repeat(line.to_string().len());
+
+		writeln!(f, "error[{}]: {}", self.kind.code(), self.kind)?;
+		match file_name {
+			Some(name) => writeln!(f, "{gutter}--> {name}:{line}:{col}")?,
+			None => writeln!(f, "{gutter}--> line {line}, column {col}")?,
+		}
+		writeln!(f, "{gutter} |")?;
+		writeln!(f, "{line} | {line_text}")?;
+
+		// Only the first line of the span is underlined. Tabs are kept in the padding so
+		// the carets line up with the text above them.
+		let padding: String = line_text
+			.chars()
+			.take(col - 1)
+			.map(|char_| if char_ == '\t' { '\t' } else { ' ' })
+			.collect();
+		let underlined = line_text.chars().skip(col - 1).count();
+		let spanned = self.src.clamped_str().trim_end_matches(['\r', '\n']);
+		let (carets, multiline) = match spanned.find('\n') {
+			Some(_) => (underlined, true),
+			None => (spanned.chars().count().min(underlined), false),
+		};
+		write!(
+			f,
+			"{gutter} | {padding}{}{}",
+			"^".repeat(carets.max(1)),
+			if multiline { "..." } else { "" }
+		)?;
+
+		if let Some(word) = self.unquoted_string() {
+			write!(
+				f,
+				"\n{gutter} = help: string values must be quoted, like `\"{word}\"`"
+			)?;
+		}
+		if self.is_truncation() {
+			match self.section {
+				Some(section) => write!(
+					f,
+					"\n{gutter} = note: the document ends unexpectedly in the `{}` table; it \
+					 may be incomplete",
+					section.clamped_str()
+				)?,
+				None => write!(
+					f,
+					"\n{gutter} = note: the document ends unexpectedly; it may be incomplete"
+				)?,
+			}
+		}
+
+		Ok(())
+	}
+}
+
+impl std::error::Error for Error<'_> {}
+
+/// An [`Error`] paired with the name of the file it came from, so the name can be shown
+/// when the error is displayed. Create one with [`Error::with_file_name()`].
+#[derive(Debug)]
+pub struct NamedError<'a> {
+	/// The file's name, or path.
+	pub name: String,
+	/// The error.
+	pub error: Error<'a>,
+}
+impl Display for NamedError<'_> {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		self.error.render(f, Some(&self.name))
+	}
+}
+impl std::error::Error for NamedError<'_> {}
+impl NamedError<'_> {
+	/// Like [`Error::to_owned_error()`], but the [`OwnedError`]'s message includes the
+	/// file's name.
+	pub fn to_owned_error(&self) -> OwnedError {
+		OwnedError {
+			message: self.to_string(),
+			..self.error.to_owned_error()
+		}
+	}
+}
+impl From<NamedError<'_>> for OwnedError {
+	fn from(err: NamedError<'_>) -> Self {
+		err.to_owned_error()
+	}
+}
+
+/// A parsing [`Error`] that doesn't borrow the TOML source, so it can outlive it - for
+/// example, to be returned with `?` from a function that reads a file into a local
+/// `String`. Create one with [`Error::into_owned()`] or [`Error::to_owned_error()`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OwnedError {
+	/// The type of parsing error; see the [`ErrorKind`] docs.
+	pub kind: ErrorKind,
+	/// The inclusive byte range of the text that caused the error.
+	pub start: usize,
+	/// See [`OwnedError::start`].
+	pub end: usize,
+	/// The line and column the error starts at. See [`Span::line_col()`].
+	pub line_col: (usize, usize),
+	/// The first line of the text that caused the error.
+	pub excerpt: String,
+	/// The line the error starts on, and the lines right before and after it.
+	pub context: String,
+	/// The header of the table the error occurred in. See [`Error::section`].
+	pub section: Option<String>,
+	/// The original error's rendered message.
+	message: String,
+}
+impl Display for OwnedError {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.write_str(&self.message)
+	}
+}
+impl std::error::Error for OwnedError {}
+impl From<Error<'_>> for OwnedError {
+	fn from(err: Error<'_>) -> Self {
+		err.to_owned_error()
+	}
+}
+
+/// A type of error while parsing TOML. New kinds may be added in minor releases, so
+/// matches on this need a wildcard arm.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
 pub enum ErrorKind {
 	/// A bare key (key without quotes) contains an invalid character.
 	InvalidBareKey,
@@ -195,6 +986,8 @@ pub enum ErrorKind {
 	NoEqualsInAssignment,
 	/// There was no key in a key/value assignment.
 	NoKeyInAssignment,
+	/// A dotted key started with a `.`, so its first key is missing.
+	LeadingDotInKey,
 	/// There was no value in a key/value assignment.
 	NoValueInAssignment,
 	/// A string literal or quoted key didn't have a closing quote.
@@ -219,6 +1012,195 @@ pub enum ErrorKind {
 	UnclosedBracket,
 	/// There was no `,` in between values in an inline table or array.
 	NoCommaDelimeter,
+	/// A string contains a control character, like a null byte, that has to be escaped.
+	ControlCharacterInString,
+	/// Something other than a comment came after a key/value pair or table header, on the
+	/// same line.
+	ExpectedNewlineAfterValue,
+	/// A date or time had a `+` or `-` sign in front of it.
+	SignedDateTime,
+	/// Arrays, tables, or dotted keys were nested more deeply than
+	/// [`ParseOptions::max_depth()`] allows.
+	NestingTooDeep,
+	/// A value was a date, time, or date-time, which boml can't parse yet.
+	UnsupportedDateTime,
+}
+
+impl Display for ErrorKind {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.write_str(match self {
+			Self::InvalidBareKey => "invalid character in bare key",
+			Self::BareKeyHasSpace => "bare keys can't contain spaces",
+			Self::NoEqualsInAssignment => "expected `=` after key",
+			Self::NoKeyInAssignment => "expected a key before `=`",
+			Self::LeadingDotInKey => "dotted keys can't start with `.`",
+			Self::NoValueInAssignment => "expected a value after `=`",
+			Self::UnclosedString => "unclosed string",
+			Self::UnrecognisedValue => "unrecognised value",
+			Self::ReusedKey => "key was already defined",
+			Self::NumberTooLarge => "number doesn't fit in a 64-bit integer",
+			Self::NumberHasInvalidBaseOrLeadingZero => {
+				"number has an invalid base or a leading zero"
+			}
+			Self::InvalidNumber => "invalid number",
+			Self::UnknownEscapeSequence => "unknown escape sequence",
+			Self::UnknownUnicodeScalar => "escape isn't a valid unicode scalar value",
+			Self::UnclosedBracket => "unclosed bracket",
+			Self::NoCommaDelimeter => "expected `,` between values",
+			Self::ControlCharacterInString => "control characters must be escaped in strings",
+			Self::ExpectedNewlineAfterValue => "expected a newline after the value",
+			Self::SignedDateTime => "dates and times can't have a sign",
+			Self::NestingTooDeep => "values are nested too deeply",
+			Self::UnsupportedDateTime => "dates and times aren't supported yet",
+		})
+	}
+}
+
+impl std::error::Error for ErrorKind {}
+// Checks that `ErrorKind::ALL` lists every kind, in the order they're declared, which is
+// also the order of their codes. The match has no wildcard arm, so a new kind doesn't compile
+// until it's given a position here, and then it has to be at that position in `ALL`.
+const _: () = {
+	const fn position(kind: ErrorKind) -> usize {
+		match kind {
+			ErrorKind::InvalidBareKey => 0,
+			ErrorKind::BareKeyHasSpace => 1,
+			ErrorKind::NoEqualsInAssignment => 2,
+			ErrorKind::NoKeyInAssignment => 3,
+			ErrorKind::LeadingDotInKey => 4,
+			ErrorKind::NoValueInAssignment => 5,
+			ErrorKind::UnclosedString => 6,
+			ErrorKind::UnrecognisedValue => 7,
+			ErrorKind::ReusedKey => 8,
+			ErrorKind::NumberTooLarge => 9,
+			ErrorKind::NumberHasInvalidBaseOrLeadingZero => 10,
+			ErrorKind::InvalidNumber => 11,
+			ErrorKind::UnknownEscapeSequence => 12,
+			ErrorKind::UnknownUnicodeScalar => 13,
+			ErrorKind::UnclosedBracket => 14,
+			ErrorKind::NoCommaDelimeter => 15,
+			ErrorKind::ControlCharacterInString => 16,
+			ErrorKind::ExpectedNewlineAfterValue => 17,
+			ErrorKind::SignedDateTime => 18,
+			ErrorKind::NestingTooDeep => 19,
+			ErrorKind::UnsupportedDateTime => 20,
+		}
+	}
+
+	let mut idx = 0;
+	while idx < ErrorKind::ALL.len() {
+		let kind = ErrorKind::ALL[idx];
+		assert!(position(kind) == idx && kind as usize == idx);
+		idx += 1;
+	}
+};
+
+impl ErrorKind {
+	/// Every kind of error, in the order of their codes.
+	pub const ALL: [Self; 21] = [
+		Self::InvalidBareKey,
+		Self::BareKeyHasSpace,
+		Self::NoEqualsInAssignment,
+		Self::NoKeyInAssignment,
+		Self::LeadingDotInKey,
+		Self::NoValueInAssignment,
+		Self::UnclosedString,
+		Self::UnrecognisedValue,
+		Self::ReusedKey,
+		Self::NumberTooLarge,
+		Self::NumberHasInvalidBaseOrLeadingZero,
+		Self::InvalidNumber,
+		Self::UnknownEscapeSequence,
+		Self::UnknownUnicodeScalar,
+		Self::UnclosedBracket,
+		Self::NoCommaDelimeter,
+		Self::ControlCharacterInString,
+		Self::ExpectedNewlineAfterValue,
+		Self::SignedDateTime,
+		Self::NestingTooDeep,
+		Self::UnsupportedDateTime,
+	];
+
+	/// A stable identifier for this kind of error, like `E0009-reused-key`. Codes are
+	/// never reused or changed, so they're safe to use in documentation links or to
+	/// suppress specific errors. They're shown when an [`Error`] is displayed.
+	pub fn code(&self) -> &'static str {
+		match self {
+			Self::InvalidBareKey => "E0001-invalid-bare-key",
+			Self::BareKeyHasSpace => "E0002-bare-key-has-space",
+			Self::NoEqualsInAssignment => "E0003-no-equals-in-assignment",
+			Self::NoKeyInAssignment => "E0004-no-key-in-assignment",
+			Self::LeadingDotInKey => "E0005-leading-dot-in-key",
+			Self::NoValueInAssignment => "E0006-no-value-in-assignment",
+			Self::UnclosedString => "E0007-unclosed-string",
+			Self::UnrecognisedValue => "E0008-unrecognised-value",
+			Self::ReusedKey => "E0009-reused-key",
+			Self::NumberTooLarge => "E0010-number-too-large",
+			Self::NumberHasInvalidBaseOrLeadingZero => "E0011-invalid-base-or-leading-zero",
+			Self::InvalidNumber => "E0012-invalid-number",
+			Self::UnknownEscapeSequence => "E0013-unknown-escape-sequence",
+			Self::UnknownUnicodeScalar => "E0014-unknown-unicode-scalar",
+			Self::UnclosedBracket => "E0015-unclosed-bracket",
+			Self::NoCommaDelimeter => "E0016-no-comma-delimiter",
+			Self::ControlCharacterInString => "E0017-control-character-in-string",
+			Self::ExpectedNewlineAfterValue => "E0018-expected-newline-after-value",
+			Self::SignedDateTime => "E0019-signed-date-time",
+			Self::NestingTooDeep => "E0020-nesting-too-deep",
+			Self::UnsupportedDateTime => "E0021-unsupported-date-time",
+		}
+	}
+
+	/// Finds the kind of error with the given code. Either the full code
+	/// (`E0009-reused-key`) or just its number (`E0009`) can be used.
+	pub fn from_code(code: &str) -> Option<Self> {
+		Self::ALL.into_iter().find(|kind| {
+			kind.code() == code
+				|| kind
+					.code()
+					.split_once('-')
+					.is_some_and(|(number, _)| number == code)
+		})
+	}
+
+	/// The general category this kind of error falls into.
+	pub fn category(&self) -> ErrorCategory {
+		match self {
+			Self::UnclosedString
+			| Self::UnknownEscapeSequence
+			| Self::UnknownUnicodeScalar
+			| Self::ControlCharacterInString => ErrorCategory::Strings,
+			Self::NumberTooLarge
+			| Self::NumberHasInvalidBaseOrLeadingZero
+			| Self::InvalidNumber => ErrorCategory::Numbers,
+			Self::SignedDateTime | Self::UnsupportedDateTime => ErrorCategory::Dates,
+			Self::InvalidBareKey
+			| Self::BareKeyHasSpace
+			| Self::NoEqualsInAssignment
+			| Self::NoKeyInAssignment
+			| Self::LeadingDotInKey
+			| Self::NoValueInAssignment
+			| Self::UnrecognisedValue
+			| Self::ReusedKey
+			| Self::UnclosedBracket
+			| Self::NoCommaDelimeter
+			| Self::ExpectedNewlineAfterValue
+			| Self::NestingTooDeep => ErrorCategory::Structure,
+		}
+	}
+}
+
+/// A group of related [`ErrorKind`]s. See [`ErrorKind::category()`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ErrorCategory {
+	/// Errors in basic or literal strings, including quoted keys.
+	Strings,
+	/// Errors in integers or floats.
+	Numbers,
+	/// Errors in date, time, or date-time values.
+	Dates,
+	/// Errors in the structure of the document - keys, assignments, tables, arrays, and
+	/// values that couldn't be recognised at all.
+	Structure,
 }
 
 mod crate_prelude {
@@ -231,9 +1213,19 @@ mod crate_prelude {
 }
 
 pub mod prelude {
+	#[cfg(feature = "miette")]
+	pub use crate::diagnostic::TomlDiagnostic;
 	pub use crate::{
+		comments::{Comment as TomlComment, CommentPlacement},
+		digest::ValidationReport,
+		document::{EditError, TomlDocument},
+		lint::{KeyPath, LintWarning, SimilarityReason},
+		options::{ParseOptions, TomlVersion},
+		owned::{LoadError, OwnedToml},
+		serializer::{TomlWriter, WriteError},
 		table::{Table as TomlTable, TomlGetError},
 		types::{TomlValue, TomlValueType},
-		Error as TomlError, ErrorKind as TomlErrorKind, Toml,
+		Error as TomlError, ErrorCategory as TomlErrorCategory, ErrorKind as TomlErrorKind,
+		NamedError as NamedTomlError, OwnedError as OwnedTomlError, Toml,
 	};
 }
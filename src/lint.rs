@@ -0,0 +1,186 @@
+//! Lints for TOML that parses fine, but probably isn't what its author meant.
+
+use {
+	crate::{crate_prelude::*, serializer, Toml},
+	std::{
+		collections::HashMap,
+		fmt::{self, Display},
+	},
+};
+
+/// The full path to a key in a document, like `package.name`.
+#[derive(Debug, Clone)]
+pub struct KeyPath<'a> {
+	/// Each part of the path, from the root table down to the key itself.
+	pub keys: Vec<&'a str>,
+	/// Where the last key in the path was defined.
+	pub span: Span<'a>,
+}
+impl Display for KeyPath<'_> {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		for (idx, key) in self.keys.iter().enumerate() {
+			if idx != 0 {
+				f.write_str(".")?;
+			}
+			serializer::write_key(f, key)?;
+		}
+		Ok(())
+	}
+}
+
+/// Why two keys were reported by [`Toml::suspicious_key_pairs()`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SimilarityReason {
+	/// The keys only differ in case, like `timeout` and `Timeout`.
+	CaseOnly,
+	/// The keys differ in their use of dashes and underscores, like `max-retries` and
+	/// `max_retries`. They may also differ in case.
+	DashUnderscore,
+	/// The exact same key is set in two sibling tables, like `[server]` and `[client]`.
+	DuplicateInSiblingSections,
+}
+
+/// A warning from [`Toml::lint()`].
+#[derive(Debug, Clone)]
+pub enum LintWarning<'a> {
+	/// Two keys look like they were meant to be the same key. See
+	/// [`Toml::suspicious_key_pairs()`].
+	SuspiciousKeys {
+		first: KeyPath<'a>,
+		second: KeyPath<'a>,
+		reason: SimilarityReason,
+	},
+}
+impl Display for LintWarning<'_> {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			Self::SuspiciousKeys {
+				first,
+				second,
+				reason,
+			} => {
+				let problem = match reason {
+					SimilarityReason::CaseOnly => "only differ in case",
+					SimilarityReason::DashUnderscore => "only differ in dashes and underscores",
+					SimilarityReason::DuplicateInSiblingSections => "are set in sibling tables",
+				};
+				let (first_line, first_col) = first.span.line_col();
+				let (second_line, second_col) = second.span.line_col();
+
+				write!(
+					f,
+					"`{first}` (line {first_line}, column {first_col}) and `{second}` (line \
+					 {second_line}, column {second_col}) {problem}"
+				)
+			}
+		}
+	}
+}
+
+impl<'a> Toml<'a> {
+	/// Finds pairs of keys that are probably meant to be the same key - keys in the same
+	/// table that only differ in case or dashes/underscores, and keys that are repeated
+	/// in sibling tables.
+	///
+	/// Arrays of tables aren't searched, since their tables are expected to repeat keys.
+	/// The pairs are sorted by where they appear in the document.
+	pub fn suspicious_key_pairs(&self) -> Vec<(KeyPath<'_>, KeyPath<'_>, SimilarityReason)> {
+		let mut census = Census::default();
+		census.visit(self, &mut Vec::new(), usize::MAX);
+
+		let mut pairs = Vec::new();
+		for entries in census.index.values() {
+			for (idx, first) in entries.iter().enumerate() {
+				for second in &entries[idx + 1..] {
+					let first_key = first.path.keys.last().unwrap();
+					let second_key = second.path.keys.last().unwrap();
+
+					let reason = if first_key == second_key {
+						if first.section == second.section || !first.leaf || !second.leaf {
+							continue;
+						}
+						SimilarityReason::DuplicateInSiblingSections
+					} else if first_key.to_lowercase() == second_key.to_lowercase() {
+						SimilarityReason::CaseOnly
+					} else {
+						SimilarityReason::DashUnderscore
+					};
+
+					let (first, second) = if first.path.span.start <= second.path.span.start {
+						(first, second)
+					} else {
+						(second, first)
+					};
+					pairs.push((first.path.clone(), second.path.clone(), reason));
+				}
+			}
+		}
+
+		pairs.sort_by_key(|(first, second, _)| (first.span.start, second.span.start));
+		pairs
+	}
+
+	/// Runs every lint on the document. See [`LintWarning`] for the possible warnings.
+	pub fn lint(&self) -> Vec<LintWarning<'_>> {
+		self.suspicious_key_pairs()
+			.into_iter()
+			.map(|(first, second, reason)| LintWarning::SuspiciousKeys {
+				first,
+				second,
+				reason,
+			})
+			.collect()
+	}
+}
+
+/// An index of every key in a document, grouped by their normalized name and the table
+/// their table is in. Keys that end up in the same group are similar to each other.
+#[derive(Default)]
+struct Census<'t> {
+	/// Maps (the ID of a table's parent table, normalized key) to every key with that
+	/// normalized name in that table or its siblings.
+	index: HashMap<(usize, String), Vec<CensusEntry<'t>>>,
+	/// The number of tables that have been visited, used to give each table an ID.
+	sections: usize,
+}
+impl<'t> Census<'t> {
+	fn visit(&mut self, table: &'t Table<'_>, path: &mut Vec<&'t str>, parent: usize) {
+		let section = self.sections;
+		self.sections += 1;
+
+		for (key, value) in table.map.iter() {
+			path.push(key.as_str());
+
+			// Keys that weren't parsed from the document have nowhere to point at, so they
+			// can't be reported
+			if let Some(&span) = key.source_span() {
+				let normalized = key.as_str().to_lowercase().replace('-', "_");
+				self.index
+					.entry((parent, normalized))
+					.or_default()
+					.push(CensusEntry {
+						path: KeyPath {
+							keys: path.clone(),
+							span,
+						},
+						section,
+						leaf: !matches!(value, TomlValue::Table(_)),
+					});
+			}
+
+			if let TomlValue::Table(child) = value {
+				self.visit(child, path, section);
+			}
+
+			path.pop();
+		}
+	}
+}
+
+struct CensusEntry<'t> {
+	path: KeyPath<'t>,
+	/// The ID of the table this key is in.
+	section: usize,
+	/// If this key's value isn't a table.
+	leaf: bool,
+}
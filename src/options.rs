@@ -0,0 +1,129 @@
+//! Defines [`ParseOptions`], for changing how TOML gets parsed.
+
+use crate::table::TableHasher;
+
+/// A version of the TOML spec. See [`ParseOptions::toml_version()`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+#[non_exhaustive]
+pub enum TomlVersion {
+	/// [TOML 1.0.0](https://toml.io/en/v1.0.0), the latest release.
+	#[default]
+	V1_0,
+	/// The TOML 1.1 draft. On top of TOML 1.0, this allows:
+	/// - The `\e` escape, for the escape character (U+001B)
+	/// - `\xHH` escapes, with exactly 2 hex digits, for characters up to U+00FF
+	V1_1,
+}
+
+/// Settings for [`Toml::parse_with()`]. The defaults match [`Toml::parse()`], and each
+/// setting can be changed with the builder methods. New settings may be added in minor
+/// releases, so this can only be created with [`ParseOptions::new()`] or
+/// [`ParseOptions::default()`]:
+///
+/// ```rust
+/// use boml::prelude::*;
+///
+/// let options = ParseOptions::new().toml_version(TomlVersion::V1_1);
+/// let toml = Toml::parse_with("escape = \"\\e[0m\"\n", &options).unwrap();
+/// assert_eq!(toml.get_string("escape"), Ok("\u{1b}[0m"));
+/// ```
+///
+/// [`Toml::parse_with()`]: crate::Toml::parse_with()
+/// [`Toml::parse()`]: crate::Toml::parse()
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct ParseOptions {
+	/// The version of the TOML spec to follow. Defaults to [`TomlVersion::V1_0`].
+	pub toml_version: TomlVersion,
+	/// How deeply arrays, tables, and dotted keys can be nested. Defaults to
+	/// [`ParseOptions::DEFAULT_MAX_DEPTH`].
+	pub max_depth: usize,
+	/// If comments should be kept, so they can be read with [`Toml::comments()`].
+	/// Defaults to `false`.
+	///
+	/// [`Toml::comments()`]: crate::Toml::comments()
+	pub capture_comments: bool,
+	/// If tables should hash their keys with [`FastHasher`] instead of SipHash.
+	/// Defaults to `false`.
+	///
+	/// [`FastHasher`]: crate::table::FastHasher
+	pub fast_hash: bool,
+}
+impl Default for ParseOptions {
+	fn default() -> Self {
+		Self {
+			toml_version: TomlVersion::default(),
+			max_depth: Self::DEFAULT_MAX_DEPTH,
+			capture_comments: false,
+			fast_hash: false,
+		}
+	}
+}
+impl ParseOptions {
+	/// The default value for [`ParseOptions::max_depth()`]. Real documents are rarely
+	/// nested more than a few levels deep, so this leaves plenty of room.
+	pub const DEFAULT_MAX_DEPTH: usize = 128;
+
+	/// The default options, which parse TOML the same way [`Toml::parse()`] does.
+	///
+	/// [`Toml::parse()`]: crate::Toml::parse()
+	#[inline]
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	/// Sets the version of the TOML spec to follow. Newer versions accept everything
+	/// older ones do, plus the additions listed in [`TomlVersion`].
+	#[inline]
+	pub fn toml_version(mut self, version: TomlVersion) -> Self {
+		self.toml_version = version;
+		self
+	}
+
+	/// Sets how deeply values can be nested. Each part of a table header or dotted key,
+	/// each array, and each inline table is another level, so `[a.b]` followed by
+	/// `c = [{ d = 1 }]` reaches a depth of 5. Going past the limit is a
+	/// [`NestingTooDeep`] error.
+	///
+	/// boml parses nested values recursively, so this keeps documents from untrusted
+	/// sources from overflowing the stack. Only raise it for documents that are trusted,
+	/// or when parsing on a thread with a large stack.
+	///
+	/// [`NestingTooDeep`]: crate::ErrorKind::NestingTooDeep
+	#[inline]
+	pub fn max_depth(mut self, depth: usize) -> Self {
+		self.max_depth = depth;
+		self
+	}
+
+	/// Sets if comments should be kept. Each comment is attached to the table header or
+	/// key/value pair it's written next to, and can be read with [`Toml::comments()`].
+	/// When this is off, which is the default, comments are skipped without being stored.
+	///
+	/// [`Toml::comments()`]: crate::Toml::comments()
+	#[inline]
+	pub fn capture_comments(mut self, capture: bool) -> Self {
+		self.capture_comments = capture;
+		self
+	}
+
+	/// Sets if tables should hash their keys with [`FastHasher`], which is faster than
+	/// the standard library's SipHash for short keys, but isn't resistant to HashDoS
+	/// attacks. Only turn this on for documents that are trusted.
+	///
+	/// [`FastHasher`]: crate::table::FastHasher
+	#[inline]
+	pub fn fast_hash(mut self, fast: bool) -> Self {
+		self.fast_hash = fast;
+		self
+	}
+
+	/// The hasher tables parsed with these options use.
+	pub(crate) fn table_hasher(&self) -> TableHasher {
+		if self.fast_hash {
+			TableHasher::fast()
+		} else {
+			TableHasher::default()
+		}
+	}
+}
@@ -0,0 +1,135 @@
+//! Defines [`OwnedToml`], a parsed TOML document that owns its source.
+
+use {
+	crate::{options::ParseOptions, OwnedError, Toml},
+	std::{
+		fmt::{self, Display},
+		fs, io,
+		path::Path,
+	},
+};
+
+/// A TOML document that owns the text it was parsed from, so it can be returned from
+/// the function that read the file:
+///
+/// ```
+/// use boml::prelude::*;
+///
+/// fn load_config(path: &std::path::Path) -> Result<OwnedToml, LoadError> {
+///     let config = OwnedToml::parse_file(path)?;
+///     Ok(config)
+/// }
+///
+/// let path = std::env::temp_dir().join("boml-owned-toml-doctest.toml");
+/// std::fs::write(&path, "[server]\nport = 8080\n").unwrap();
+///
+/// let config = load_config(&path).unwrap();
+/// let toml = config.toml();
+/// let server = toml.get_table("server").unwrap();
+/// assert_eq!(server.get_integer("port"), Ok(8080));
+/// # std::fs::remove_file(&path).unwrap();
+/// ```
+///
+/// Since a [`Toml<'_>`] borrows from its source, an [`OwnedToml`] only stores the
+/// source, after checking that it's valid. [`OwnedToml::toml()`] parses it again each
+/// time it's called, so call it once and keep the [`Toml<'_>`] around while reading
+/// values.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OwnedToml {
+	source: String,
+	options: ParseOptions,
+}
+impl OwnedToml {
+	/// Checks that `source` is valid TOML, and takes ownership of it.
+	#[inline]
+	pub fn parse(source: impl Into<String>) -> Result<Self, LoadError> {
+		Self::parse_with(source, &ParseOptions::default())
+	}
+
+	/// Checks that `source` is valid TOML with the provided [`ParseOptions`], and takes
+	/// ownership of it. The options are used again by [`OwnedToml::toml()`].
+	pub fn parse_with(
+		source: impl Into<String>,
+		options: &ParseOptions,
+	) -> Result<Self, LoadError> {
+		let source = source.into();
+		if let Err(err) = Toml::parse_with(&source, options) {
+			return Err(LoadError::Parse(Box::new(err.into_owned())));
+		}
+
+		Ok(Self {
+			source,
+			options: *options,
+		})
+	}
+
+	/// Reads the file at `path`, and checks that it's valid TOML. Parsing errors include
+	/// the path when they're displayed.
+	pub fn parse_file(path: impl AsRef<Path>) -> Result<Self, LoadError> {
+		let path = path.as_ref();
+		let source = fs::read_to_string(path)?;
+		if let Err(err) = Toml::parse(&source) {
+			let err = err.with_file_name(path.display().to_string());
+			return Err(LoadError::Parse(Box::new(err.to_owned_error())));
+		}
+
+		Ok(Self {
+			source,
+			options: ParseOptions::default(),
+		})
+	}
+
+	/// Parses the source again, returning a [`Toml<'_>`] that borrows from this
+	/// [`OwnedToml`].
+	pub fn toml(&self) -> Toml<'_> {
+		Toml::parse_with(&self.source, &self.options)
+			.expect("The source was already parsed successfully")
+	}
+
+	/// The text this TOML was parsed from.
+	#[inline]
+	pub fn source(&self) -> &str {
+		&self.source
+	}
+
+	/// Consumes the [`OwnedToml`], returning the text it was parsed from.
+	#[inline]
+	pub fn into_source(self) -> String {
+		self.source
+	}
+}
+
+/// Errors from creating an [`OwnedToml`].
+#[derive(Debug)]
+pub enum LoadError {
+	/// The file couldn't be read, or wasn't UTF-8.
+	Io(io::Error),
+	/// The text wasn't valid TOML.
+	Parse(Box<OwnedError>),
+}
+impl Display for LoadError {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			Self::Io(err) => write!(f, "failed to read TOML: {err}"),
+			Self::Parse(err) => Display::fmt(err, f),
+		}
+	}
+}
+impl std::error::Error for LoadError {
+	fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+		match self {
+			Self::Io(err) => Some(err),
+			Self::Parse(err) => Some(&**err),
+		}
+	}
+}
+impl From<io::Error> for LoadError {
+	fn from(err: io::Error) -> Self {
+		Self::Io(err)
+	}
+}
+impl From<OwnedError> for LoadError {
+	fn from(err: OwnedError) -> Self {
+		Self::Parse(Box::new(err))
+	}
+}
@@ -2,52 +2,99 @@
 //!
 //! Parser rules:
 //! 1. Each parser is only responsible for the length of the data it parses. Extraneous whitespace,
-//! comments, or invalid characters fall outside the scope of the parsers.
+//!    comments, or invalid characters fall outside the scope of the parsers.
 //! 2. Parsers assume that the current index in the [`Text`] is the first character of what they
-//! should parse - ie, the first letter of a key, opening quote of a quoted key, opening bracket
-//! of a table, etc.
+//!    should parse - ie, the first letter of a key, opening quote of a quoted key, opening bracket
+//!    of a table, etc.
 //! 3. Each parser should leave `text.idx` at the last byte it parsed.
 
-use {crate::crate_prelude::*, std::num::IntErrorKind};
+use {
+	crate::{crate_prelude::*, options::TomlVersion, table::Definition, text::is_bare_key_byte},
+	std::num::IntErrorKind,
+};
 
 /// Parses a `<key> = <value>` assignment.
-pub fn parse_assignment<'a>(text: &mut Text<'a>) -> Result<(Key<'a>, TomlValue<'a>), Error> {
+pub fn parse_assignment<'a>(text: &mut Text<'a>) -> Result<(Key<'a>, TomlValue<'a>), Error<'a>> {
+	// Assignments without a key, like `= 5` or `.a = 1`. The whole line is consumed, so
+	// parsing can resume on the next line.
+	if let Some(byte @ (b'=' | b'.')) = text.current_byte() {
+		let start = text.idx;
+		let mut end = text
+			.excerpt(start..)
+			.find(b'\n')
+			.map(|newline| newline - 1)
+			.unwrap_or(text.end());
+		if text.byte(end) == Some(b'\r') && end > start {
+			end -= 1;
+		}
+		text.idx = end;
+
+		return Err(Error {
+			src: text.excerpt(start..=end),
+			kind: if byte == b'=' {
+				ErrorKind::NoKeyInAssignment
+			} else {
+				ErrorKind::LeadingDotInKey
+			},
+			section: None,
+		});
+	}
+
+	// Each part of the key is another level of nesting, but only for this value
+	let depth = text.depth;
 	let key = parse_key(text)?;
 
 	text.idx += 1;
 	text.skip_whitespace();
 	if text.current_byte() != Some(b'=') {
 		return Err(Error {
-			start: key.text.span().start,
-			end: text.idx,
+			src: text.excerpt(key.span().start..=text.idx),
 			kind: ErrorKind::NoEqualsInAssignment,
+			section: None,
 		});
 	}
 	text.idx += 1;
 	text.skip_whitespace();
-	if text.idx >= text.end() {
+	// The value can't be missing, or on another line
+	if matches!(text.current_byte(), None | Some(b'\n' | b'\r' | b'#')) {
+		let mut end = text.idx;
+		while matches!(text.byte(end - 1), Some(b' ' | b'\t')) {
+			end -= 1;
+		}
+
 		return Err(Error {
-			start: key.text.span().start,
-			end: text.idx,
+			src: text.excerpt(key.span().start..end),
 			kind: ErrorKind::NoValueInAssignment,
+			section: None,
 		});
 	}
 
 	let value = parse_value(text)?;
+	text.depth = depth;
 
 	Ok((key, value))
 }
 
-/// Parses a key. Supports quoted, dotted, and bare keys.
-pub fn parse_key<'a>(text: &mut Text<'a>) -> Result<Key<'a>, Error> {
-	let maybe_key = match text.current_byte().unwrap() {
-		b'\'' | b'"' => parse_string(text)?,
-		_ => {
+/// Parses a key. Supports quoted, dotted, and bare keys. Each part of the key is a level
+/// of nesting; see [`Text::nest()`].
+pub fn parse_key<'a>(text: &mut Text<'a>) -> Result<Key<'a>, Error<'a>> {
+	text.nest(text.idx)?;
+	let maybe_key = match text.current_byte() {
+		Some(b'\'' | b'"') => parse_string(text)?,
+		// Text shouldn't end where a key is expected, like after the `.` in `a.`
+		None => {
+			return Err(Error {
+				src: text.excerpt(text.idx.saturating_sub(1)..text.idx),
+				kind: ErrorKind::NoValueInAssignment,
+				section: None,
+			})
+		}
+		Some(_) => {
 			let start = text.idx;
 			let mut current = text.idx;
 
 			while let Some(byte) = text.byte(current) {
-				if !byte.is_ascii_alphanumeric() && byte != b'-' && byte != b'_' {
+				if !is_bare_key_byte(byte) {
 					break;
 				}
 
@@ -57,17 +104,27 @@ pub fn parse_key<'a>(text: &mut Text<'a>) -> Result<Key<'a>, Error> {
 			if text.byte(current).is_none() {
 				// Text shouldn't end on a key definition
 				return Err(Error {
-					start,
-					end: current,
+					src: text.excerpt(start..=current),
 					kind: ErrorKind::NoValueInAssignment,
+					section: None,
 				});
 			}
-			if start == current {
-				// Empty bare keys are not allowed
+			if start == current
+				|| !matches!(
+					text.byte(current),
+					Some(b' ' | b'\t' | b'=' | b'.' | b']' | b'\r' | b'\n' | b'#')
+				) {
+				// Empty bare keys are not allowed, and neither are keys that run straight
+				// into a character that can't be in bare keys, like `12ab!`, so point at
+				// that character
+				let len = text.text[current..]
+					.chars()
+					.next()
+					.map_or(1, char::len_utf8);
 				return Err(Error {
-					start,
-					end: current,
+					src: text.excerpt(current..current + len),
 					kind: ErrorKind::InvalidBareKey,
+					section: None,
 				});
 			}
 
@@ -79,7 +136,7 @@ pub fn parse_key<'a>(text: &mut Text<'a>) -> Result<Key<'a>, Error> {
 	};
 
 	// Check for dotted key
-	let key_end = text.idx;
+	let key_end = text.checkpoint();
 	text.idx += 1;
 	text.skip_whitespace();
 	if text.current_byte() == Some(b'.') {
@@ -91,7 +148,7 @@ pub fn parse_key<'a>(text: &mut Text<'a>) -> Result<Key<'a>, Error> {
 			child: Some(Box::new(parse_key(text)?)),
 		})
 	} else {
-		text.idx = key_end;
+		text.rewind(key_end);
 		Ok(Key {
 			text: maybe_key,
 			child: None,
@@ -100,56 +157,56 @@ pub fn parse_key<'a>(text: &mut Text<'a>) -> Result<Key<'a>, Error> {
 }
 
 /// Parses a value. Supports all of the non-time-related value types.
-pub fn parse_value<'a>(text: &mut Text<'a>) -> Result<TomlValue<'a>, Error> {
-	match text.current_byte().unwrap() {
-		// Integer, time, or float
-		b'0'..=b'9' | b'i' | b'n' => parse_num(text, false),
-
-		// Integer or float with +/- modifier
-		b'+' if text.remaining_bytes() > 0 => {
-			text.idx += 1;
+pub fn parse_value<'a>(text: &mut Text<'a>) -> Result<TomlValue<'a>, Error<'a>> {
+	let start = text.idx;
 
-			parse_num(text, false)
+	match text.current_byte().unwrap() {
+		// Integer, time, or float. Floats can't start with `.`, but they're still parsed as
+		// numbers so the error says what's wrong with them.
+		b'0'..=b'9' | b'i' | b'n' | b'.' => {
+			let num = parse_num(text)?;
+			expect_value_end(text, start)?;
+			Ok(num)
 		}
-		b'-' if text.remaining_bytes() > 0 => {
-			text.idx += 1;
 
-			parse_num(text, true)
-		}
+		// Integer or float with +/- modifier. If it isn't a number, the text is left at
+		// the sign.
+		b'+' | b'-' if text.remaining_bytes() > 0 => text.speculate(|text| {
+			let num = parse_num(text)?;
+			expect_value_end(text, start)?;
+			Ok(num)
+		}),
 
 		// String
 		b'\'' | b'"' => parse_string(text).map(TomlValue::String),
 
 		// Bool
 		b't' | b'f' if text.remaining_bytes() >= 3 => {
-			let span = text.excerpt(text.idx..text.idx + 4);
-			if span.as_str() == "true" {
-				text.idx = span.end;
+			if text.starts_with("true") {
+				text.idx += 3;
+				expect_value_end(text, start)?;
 				return Ok(TomlValue::Boolean(true));
-			} else if span.as_str() == "fals" && text.byte(text.idx + 4) == Some(b'e') {
-				text.idx = span.end + 1;
+			} else if text.starts_with("false") {
+				text.idx += 4;
+				expect_value_end(text, start)?;
 				return Ok(TomlValue::Boolean(false));
 			}
 
-			let span = text.excerpt(text.idx..);
-			Err(Error {
-				start: span.start,
-				end: span.find_next_whitespace_or_newline().unwrap_or(text.end()),
-				kind: ErrorKind::UnrecognisedValue,
-			})
+			Err(unrecognised_value(text, start))
 		}
 
 		// Array
 		b'[' => {
 			if text.remaining_bytes() == 0 {
 				return Err(Error {
-					start: text.idx,
-					end: text.idx,
+					src: text.excerpt(text.idx..=text.idx),
 					kind: ErrorKind::UnclosedBracket,
+					section: None,
 				});
 			}
 
-			let mut array = Vec::new();
+			text.nest(text.idx)?;
+			let mut array = Vec::with_capacity(text.estimate_items());
 			let mut span = text.excerpt(text.idx..);
 			let mut seen_comma = true;
 			text.idx += 1;
@@ -159,14 +216,22 @@ pub fn parse_value<'a>(text: &mut Text<'a>) -> Result<TomlValue<'a>, Error> {
 
 				match text.current_byte() {
 					Some(b']') => break,
+					// Commas have to come after a value, so `[,]` and `[1,,2]` are invalid
+					Some(b',') if seen_comma => {
+						return Err(Error {
+							src: text.excerpt(text.idx..=text.idx),
+							kind: ErrorKind::UnrecognisedValue,
+							section: None,
+						})
+					}
 					Some(b',') => {
 						text.idx += 1;
 						text.skip_whitespace_and_newlines();
-						if text.remaining_bytes() == 0 {
+						if text.current_byte().is_none() {
 							return Err(Error {
-								start: span.start,
-								end: text.idx,
+								src: text.excerpt(span.start..=text.idx),
 								kind: ErrorKind::UnclosedBracket,
+								section: None,
 							});
 						}
 
@@ -174,24 +239,28 @@ pub fn parse_value<'a>(text: &mut Text<'a>) -> Result<TomlValue<'a>, Error> {
 						continue;
 					}
 					Some(b'#') => {
-						text.idx = text.excerpt(text.idx..).find(b'\n').unwrap_or(text.end());
+						// If there's no newline, the comment is at the end of the file
+						text.idx = text
+							.excerpt(text.idx..)
+							.find(b'\n')
+							.unwrap_or(text.text.len());
 						text.skip_whitespace_and_newlines();
 
 						continue;
 					}
 					Some(_) if !seen_comma => {
 						return Err(Error {
-							start: text.idx,
-							end: text.idx,
+							src: text.excerpt(text.idx..=text.idx),
 							kind: ErrorKind::NoCommaDelimeter,
+							section: None,
 						})
 					}
 					Some(_) => {}
 					None => {
 						return Err(Error {
-							start: span.start,
-							end: text.idx,
+							src: text.excerpt(span.start..=text.idx),
 							kind: ErrorKind::UnclosedBracket,
+							section: None,
 						})
 					}
 				}
@@ -204,6 +273,7 @@ pub fn parse_value<'a>(text: &mut Text<'a>) -> Result<TomlValue<'a>, Error> {
 				seen_comma = false;
 			}
 
+			text.depth -= 1;
 			Ok(TomlValue::Array(array))
 		}
 
@@ -211,56 +281,58 @@ pub fn parse_value<'a>(text: &mut Text<'a>) -> Result<TomlValue<'a>, Error> {
 		b'{' => {
 			if text.remaining_bytes() == 0 {
 				return Err(Error {
-					start: text.idx,
-					end: text.idx,
+					src: text.excerpt(text.idx..=text.idx),
 					kind: ErrorKind::UnclosedBracket,
+					section: None,
 				});
 			}
 
-			let mut table = Table::default();
+			text.nest(text.idx)?;
+			let mut table = Table {
+				definition: Definition::Inline,
+				..Table::with_hasher(text.estimate_items(), text.options.table_hasher())
+			};
 			let mut span = text.excerpt(text.idx..);
 
 			text.idx += 1;
+			let mut comma = None;
 
 			loop {
 				text.skip_whitespace();
 
-				// Empty table
 				if text.current_byte() == Some(b'}') {
+					// Inline tables can't have trailing commas, like `{ a = 1, }`
+					if let Some(comma) = comma {
+						return Err(Error {
+							src: text.excerpt(comma..=comma),
+							kind: ErrorKind::UnrecognisedValue,
+							section: None,
+						});
+					}
 					break;
 				}
 
 				let (key, value) = parse_assignment(text)?;
-				let start = key.text.span().start;
-				let end = key.text.span().end;
-
-				let old_value = table.insert(key, value);
-				if old_value {
-					return Err(Error {
-						start,
-						end,
-						kind: ErrorKind::ReusedKey,
-					});
-				}
+				table.insert(key, value)?;
 				span.end = text.idx;
 
 				text.idx += 1;
 				text.skip_whitespace();
 				match text.current_byte() {
 					Some(b'}') => break,
-					Some(b',') => {}
+					Some(b',') => comma = Some(text.idx),
 					Some(_) => {
 						return Err(Error {
-							start: text.idx,
-							end: text.idx,
+							src: text.excerpt(text.idx..=text.idx),
 							kind: ErrorKind::NoCommaDelimeter,
+							section: None,
 						})
 					}
 					None => {
 						return Err(Error {
-							start: span.start,
-							end: span.end,
+							src: text.excerpt(span.start..=text.end()),
 							kind: ErrorKind::UnclosedBracket,
+							section: None,
 						})
 					}
 				}
@@ -268,22 +340,76 @@ pub fn parse_value<'a>(text: &mut Text<'a>) -> Result<TomlValue<'a>, Error> {
 				text.idx += 1;
 			}
 
+			text.depth -= 1;
 			Ok(TomlValue::Table(table))
 		}
 
 		// ¯\_(ツ)_/¯
-		_ => {
-			let span = text.excerpt(text.idx..);
-			Err(Error {
-				start: span.start,
-				end: span.find_next_whitespace_or_newline().unwrap_or(text.end()),
-				kind: ErrorKind::UnrecognisedValue,
-			})
+		_ => Err(unrecognised_value(text, start)),
+	}
+}
+
+/// Checks that the value that was just parsed is followed by something that can come
+/// after a value - whitespace, a comment, a delimiter, or the end of the text. Otherwise,
+/// the value is just the start of something else, like `12ab` or `trueish`, and the whole
+/// thing is reported as an unrecognised value.
+fn expect_value_end<'a>(text: &Text<'a>, start: usize) -> Result<(), Error<'a>> {
+	match text.byte(text.idx + 1) {
+		None | Some(b' ' | b'\t' | b'\r' | b'\n' | b',' | b']' | b'}' | b'#') => Ok(()),
+		Some(_) => Err(unrecognised_value(text, start)),
+	}
+}
+
+/// Checks that nothing but whitespace or a comment comes between `idx` and the end of the
+/// line, as TOML requires after key/value pairs and table headers. `idx` should be the
+/// first byte after the value or header.
+pub(crate) fn expect_line_end<'a>(text: &Text<'a>, mut idx: usize) -> Result<(), Error<'a>> {
+	while matches!(text.byte(idx), Some(b' ' | b'\t')) {
+		idx += 1;
+	}
+
+	match text.byte(idx) {
+		None | Some(b'\n' | b'#') => Ok(()),
+		Some(b'\r') if text.byte(idx + 1) == Some(b'\n') => Ok(()),
+		Some(_) => Err(Error {
+			kind: ErrorKind::ExpectedNewlineAfterValue,
+			..unrecognised_value(text, idx)
+		}),
+	}
+}
+
+/// An [`ErrorKind::UnrecognisedValue`] error, covering the value starting at `start` up to
+/// the next whitespace, comment, or delimiter.
+fn unrecognised_value<'a>(text: &Text<'a>, start: usize) -> Error<'a> {
+	let mut end = start;
+	while let Some(byte) = text.byte(end) {
+		if matches!(
+			byte,
+			b' ' | b'\t' | b'\r' | b'\n' | b',' | b']' | b'}' | b'#'
+		) {
+			break;
 		}
+		end += 1;
+	}
+
+	Error {
+		src: text.excerpt(start..end.max(start + 1)),
+		kind: ErrorKind::UnrecognisedValue,
+		section: None,
 	}
 }
 
-fn parse_num<'a>(text: &mut Text<'a>, negative: bool) -> Result<TomlValue<'a>, Error> {
+/// Parses a number - an integer, float, or time - including the `+` or `-` before it, if
+/// it has one.
+fn parse_num<'a>(text: &mut Text<'a>) -> Result<TomlValue<'a>, Error<'a>> {
+	let start = text.idx;
+	let sign = text
+		.current_byte()
+		.filter(|byte| matches!(byte, b'+' | b'-'));
+	if sign.is_some() {
+		text.idx += 1;
+	}
+	let negative = sign == Some(b'-');
 	let mut span = Span {
 		start: text.idx,
 		end: text.idx,
@@ -292,17 +418,16 @@ fn parse_num<'a>(text: &mut Text<'a>, negative: bool) -> Result<TomlValue<'a>, E
 
 	// inf or nan
 	let current_byte = text.current_byte().unwrap();
-	if (current_byte == b'i' || current_byte == b'n') && text.remaining_bytes() >= 2 {
-		span.end += 2;
-		if span.as_str() == "inf" {
-			text.idx = span.end;
+	if current_byte == b'i' || current_byte == b'n' {
+		if text.starts_with("inf") {
+			text.idx += 2;
 			if negative {
 				return Ok(TomlValue::Float(-f64::INFINITY));
 			} else {
 				return Ok(TomlValue::Float(f64::INFINITY));
 			}
-		} else if span.as_str() == "nan" {
-			text.idx = span.end;
+		} else if text.starts_with("nan") {
+			text.idx += 2;
 			if negative {
 				return Ok(TomlValue::Float(-f64::NAN));
 			} else {
@@ -369,6 +494,28 @@ fn parse_num<'a>(text: &mut Text<'a>, negative: bool) -> Result<TomlValue<'a>, E
 		None
 	};
 
+	// Only decimal numbers can have a sign
+	if radix.is_some() && sign.is_some() {
+		return Err(Error {
+			kind: ErrorKind::NumberHasInvalidBaseOrLeadingZero,
+			..unrecognised_value(text, start)
+		});
+	}
+
+	// Digits that don't belong to the base, like the `8` in `0o78`
+	let next = span.end + 1;
+	if radix.is_some()
+		&& text
+			.byte(next)
+			.is_some_and(|byte| byte.is_ascii_alphanumeric())
+	{
+		return Err(Error {
+			src: text.excerpt(next..=next),
+			kind: ErrorKind::InvalidNumber,
+			section: None,
+		});
+	}
+
 	if radix.is_none() {
 		let mut has_dash = false;
 
@@ -390,21 +537,34 @@ fn parse_num<'a>(text: &mut Text<'a>, negative: bool) -> Result<TomlValue<'a>, E
 			span.end += 1;
 		}
 
-		if is_float && is_time {
+		if !is_float && has_dash {
+			is_time = true;
+		}
+
+		// Dates and times can't have signs, even though their digits look like numbers
+		if is_time && sign.is_some() {
 			return Err(Error {
-				start: span.start,
-				end: span.end,
+				kind: ErrorKind::SignedDateTime,
+				..unrecognised_value(text, start)
+			});
+		} else if is_float && is_time {
+			return Err(Error {
+				src: span,
 				kind: ErrorKind::InvalidNumber,
+				section: None,
 			});
-		} else if !is_float && has_dash {
-			is_time = true;
 		}
 	}
 
+	// The whole number, including its radix prefix, for errors
+	let literal = span;
 	if radix.is_some() {
 		span.start += 2;
 	}
 	text.idx = span.end;
+	if !is_time {
+		check_number_syntax(text, literal, radix)?;
+	}
 
 	let source = if has_underscores {
 		let mut string = String::with_capacity(span.len());
@@ -418,11 +578,18 @@ fn parse_num<'a>(text: &mut Text<'a>, negative: bool) -> Result<TomlValue<'a>, E
 	} else {
 		CowSpan::Raw(span)
 	};
-	let span = source.span();
-
 	if is_float {
 		// Unfortunately, the f64 parser doesn't give detailed error information, so this is the best we can do.
 		if let Ok(num) = source.as_str().parse::<f64>() {
+			// Rust rounds numbers that are too big to infinity, but `inf` has to be
+			// written explicitly in TOML
+			if num.is_infinite() {
+				return Err(Error {
+					src: literal,
+					kind: ErrorKind::NumberTooLarge,
+					section: None,
+				});
+			}
 			if negative {
 				return Ok(TomlValue::Float(-num));
 			} else {
@@ -431,8 +598,12 @@ fn parse_num<'a>(text: &mut Text<'a>, negative: bool) -> Result<TomlValue<'a>, E
 		}
 	}
 
-	if is_time && !negative {
-		todo!("Time types")
+	if is_time {
+		return Err(Error {
+			src: literal,
+			kind: ErrorKind::UnsupportedDateTime,
+			section: None,
+		});
 	}
 
 	match i64::from_str_radix(source.as_str(), radix.unwrap_or(10)) {
@@ -451,45 +622,114 @@ fn parse_num<'a>(text: &mut Text<'a>, negative: bool) -> Result<TomlValue<'a>, E
 				}
 
 				return Err(Error {
-					start: span.start,
-					end: span.end,
+					src: literal,
 					kind: ErrorKind::NumberTooLarge,
+					section: None,
 				});
 			}
 			IntErrorKind::InvalidDigit => {}
-			IntErrorKind::Empty => {
-				return Err(Error {
-					start: span.start,
-					end: span.end,
-					kind: ErrorKind::InvalidNumber,
-				})
-			}
+			// Radix prefixes without any digits are caught by `check_number_syntax()`
+			IntErrorKind::Empty => unreachable!(),
 			_ => unreachable!(),
 		},
 	}
 
-	Err(Error {
-		start: span.start,
-		end: span.find_next_whitespace_or_newline().unwrap_or(text.end()),
-		kind: ErrorKind::UnrecognisedValue,
-	})
+	Err(unrecognised_value(text, literal.start))
+}
+
+/// Checks the parts of a number's syntax that Rust's number parsers are more lenient
+/// about than TOML. `literal` is the whole number, including its radix prefix, if it has
+/// one:
+/// - Every underscore has to be between two digits. Errors for misplaced underscores
+///   point at the underscore.
+/// - Decimal numbers can't have leading zeros, like `0123`.
+/// - The decimal point in a float needs a digit on both sides, so `.5`, `1.`, and `1.e5`
+///   are invalid.
+/// - Exponents need at least one digit, after an optional sign, so `1e` and `1e+` are
+///   invalid.
+fn check_number_syntax<'a>(
+	text: &Text<'a>,
+	literal: Span<'a>,
+	radix: Option<u32>,
+) -> Result<(), Error<'a>> {
+	let bytes = literal.as_bytes();
+	let digits = if radix.is_some() { &bytes[2..] } else { bytes };
+	let digits_start = literal.start + bytes.len() - digits.len();
+	let is_digit = |byte: Option<&u8>| match byte {
+		Some(byte) if radix == Some(16) => byte.is_ascii_hexdigit(),
+		Some(byte) => byte.is_ascii_digit(),
+		None => false,
+	};
+	// Not a number at all, like `+x`, unless it's a float that's missing its leading
+	// digit, like `.5`
+	if radix.is_none() && !is_digit(digits.first()) {
+		if digits.first() == Some(&b'.') && is_digit(digits.get(1)) {
+			return Err(Error {
+				kind: ErrorKind::InvalidNumber,
+				..unrecognised_value(text, literal.start)
+			});
+		}
+		return Err(unrecognised_value(text, literal.start));
+	}
+
+	let misplaced_underscore = digits.iter().enumerate().position(|(idx, byte)| {
+		*byte == b'_'
+			&& (idx == 0 || !is_digit(digits.get(idx - 1)) || !is_digit(digits.get(idx + 1)))
+	});
+	if let Some(idx) = misplaced_underscore {
+		return Err(Error {
+			src: text.excerpt(digits_start + idx..=digits_start + idx),
+			kind: ErrorKind::InvalidNumber,
+			section: None,
+		});
+	}
+
+	let integer_part = digits
+		.iter()
+		.position(|byte| matches!(byte, b'.' | b'e' | b'E'))
+		.map_or(digits, |end| &digits[..end]);
+	if radix.is_none() && integer_part.len() > 1 && integer_part[0] == b'0' {
+		return Err(Error {
+			kind: ErrorKind::NumberHasInvalidBaseOrLeadingZero,
+			..unrecognised_value(text, literal.start)
+		});
+	}
+
+	let valid_point = digits
+		.iter()
+		.position(|byte| *byte == b'.')
+		.map_or(true, |point| is_digit(digits.get(point + 1)));
+	// `e` is a digit in hexadecimal numbers, so only decimal numbers have exponents
+	let valid_exponent = radix.is_some()
+		|| digits
+			.iter()
+			.position(|byte| matches!(byte, b'e' | b'E'))
+			.map_or(true, |exponent| {
+				let sign = matches!(digits.get(exponent + 1), Some(b'+' | b'-'));
+				is_digit(digits.get(exponent + 1 + sign as usize))
+			});
+	if is_digit(digits.first()) && valid_point && valid_exponent {
+		Ok(())
+	} else {
+		Err(Error {
+			kind: ErrorKind::InvalidNumber,
+			..unrecognised_value(text, literal.start)
+		})
+	}
 }
 
 /// Parses a string. Supports literal and basic strings. Handles basic string escapes
 /// automatically.
-pub fn parse_string<'a>(text: &mut Text<'a>) -> Result<CowSpan<'a>, Error> {
+pub fn parse_string<'a>(text: &mut Text<'a>) -> Result<CowSpan<'a>, Error<'a>> {
 	let mut span = text.excerpt(text.idx..);
 
 	match text.current_byte().unwrap() {
 		b'\'' => {
-			let (end, offset) = if text.remaining_bytes() > 5
-				&& text.excerpt(text.idx..text.idx + 3).to_str() == "'''"
-			{
+			let multiline = text.starts_with("'''");
+			let (end, offset) = if multiline {
 				// Multi-line string
 				span.start += 3;
-				if text.byte(span.start).unwrap() == b'\n' {
-					span.start += 1;
-				}
+				span.start += leading_newline(text, span.start);
 				(
 					span.as_str().find("'''").map(|idx| {
 						let mut idx = span.start + idx;
@@ -508,39 +748,34 @@ pub fn parse_string<'a>(text: &mut Text<'a>) -> Result<CowSpan<'a>, Error> {
 				(span.find(b'\''), 1)
 			};
 
-			let Some(end) = end else {
-				return Err(Error {
-					start: text.idx,
-					end: span.find_next_whitespace_or_newline().unwrap_or(text.end()),
-					kind: ErrorKind::UnclosedString,
-				});
+			let Some(end) = end.filter(|end| multiline || *end < line_end(text)) else {
+				return Err(unclosed_string(text, multiline));
 			};
 			span.end = end - 1;
 			text.idx = span.end + offset;
+			check_control_characters(span)?;
 
 			Ok(CowSpan::Raw(span))
 		}
 		b'"' => {
-			let multiline = text.remaining_bytes() > 5
-				&& text.excerpt(text.idx..text.idx + 3).to_str() == "\"\"\"";
+			let multiline = text.starts_with("\"\"\"");
 			let offset = if multiline { 3 } else { 1 };
 			let start = span.start;
 
-			let Some(end) = find_basic_string_end(&mut span, text, multiline) else {
-				return Err(Error {
-					start: text.idx,
-					end: span.find_next_whitespace_or_newline().unwrap_or(text.end()),
-					kind: ErrorKind::UnclosedString,
-				});
+			let Some(end) = find_basic_string_end(&mut span, text, multiline)
+				.filter(|end| multiline || *end < line_end(text))
+			else {
+				return Err(unclosed_string(text, multiline));
 			};
 			span.start = start + offset;
 			span.end = end - 1;
 
-			if multiline && text.byte(span.start).unwrap() == b'\n' {
-				span.start += 1;
+			if multiline {
+				span.start += leading_newline(text, span.start);
 			}
 
 			text.idx = span.end + offset;
+			check_control_characters(span)?;
 
 			if span.find(b'\\').is_some() {
 				handle_basic_string_escapes(text, span)
@@ -552,11 +787,83 @@ pub fn parse_string<'a>(text: &mut Text<'a>) -> Result<CowSpan<'a>, Error> {
 	}
 }
 
-fn find_basic_string_end(span: &mut Span<'_>, text: &Text<'_>, multiline: bool) -> Option<usize> {
+/// The index of the first newline after `text.idx`, or the end of the text if there are
+/// no more newlines. Single-line strings have to end before this.
+fn line_end(text: &Text<'_>) -> usize {
+	text.excerpt(text.idx..)
+		.find(b'\n')
+		.unwrap_or(text.text.len())
+}
+
+/// The error for a string starting at `text.idx` that's missing its closing delimiter.
+/// Single-line strings can't contain line breaks, so the error only covers the rest of
+/// the line, instead of running on to wherever the next quote happens to be.
+fn unclosed_string<'a>(text: &Text<'a>, multiline: bool) -> Error<'a> {
 	let end = if multiline {
-		// Multi-line string
-		span.start += 3;
-		span.as_str().find("\"\"\"").map(|idx| {
+		text.text.len()
+	} else {
+		let end = line_end(text);
+		if end > text.idx && text.byte(end - 1) == Some(b'\r') {
+			end - 1
+		} else {
+			end
+		}
+	};
+
+	Error {
+		src: text.excerpt(text.idx..end),
+		kind: ErrorKind::UnclosedString,
+		section: None,
+	}
+}
+
+/// The length of the line break at `idx`, if there is one. Multiline strings trim a line
+/// break right after their opening delimiter, whether it's an LF or a CRLF.
+fn leading_newline(text: &Text<'_>, idx: usize) -> usize {
+	match (text.byte(idx), text.byte(idx + 1)) {
+		(Some(b'\n'), _) => 1,
+		(Some(b'\r'), Some(b'\n')) => 2,
+		_ => 0,
+	}
+}
+
+/// Checks that the contents of a string don't contain any control characters, which have
+/// to be escaped in TOML. Tabs are allowed, and so are line breaks, which are checked
+/// separately. A carriage return is only a line break as part of a CRLF, so a lone one is
+/// a control character. Line breaks in multiline strings are kept as they're written, so
+/// CRLFs stay CRLFs.
+fn check_control_characters(contents: Span<'_>) -> Result<(), Error<'_>> {
+	let bytes = contents.source.as_bytes();
+
+	for idx in contents.start..=contents.end {
+		let is_control = match bytes.get(idx) {
+			Some(0x00..=0x08 | 0x0B | 0x0C | 0x0E..=0x1F | 0x7F) => true,
+			Some(b'\r') => bytes.get(idx + 1) != Some(&b'\n'),
+			_ => false,
+		};
+
+		if is_control {
+			return Err(Error {
+				src: Span {
+					start: idx,
+					end: idx,
+					source: contents.source,
+				},
+				kind: ErrorKind::ControlCharacterInString,
+				section: None,
+			});
+		}
+	}
+
+	Ok(())
+}
+
+fn find_basic_string_end(span: &mut Span<'_>, text: &Text<'_>, multiline: bool) -> Option<usize> {
+	loop {
+		let end = if multiline {
+			// Multi-line string
+			span.start += 3;
+			let idx = span.as_str().find("\"\"\"")?;
 			let mut idx = span.start + idx;
 
 			while text.byte(idx) == Some(b'"') {
@@ -564,124 +871,159 @@ fn find_basic_string_end(span: &mut Span<'_>, text: &Text<'_>, multiline: bool)
 			}
 
 			idx - 3
-		})
-	} else {
-		// Single-line string
-		span.start += 1;
-		span.find(b'"')
-	};
-
-	if let Some(end) = end {
-		if text.byte(end - 1).unwrap() == b'\\' && text.byte(end - 2).unwrap() != b'\\' {
-			span.start = end;
-			find_basic_string_end(span, text, multiline)
 		} else {
-			Some(end)
+			// Single-line string
+			span.start += 1;
+			span.find(b'"')?
+		};
+
+		// The quote is escaped if it's preceded by an odd number of backslashes, in which
+		// case the search starts again after it
+		let mut backslashes = 0;
+		while text.byte(end - backslashes - 1) == Some(b'\\') {
+			backslashes += 1;
 		}
-	} else {
-		None
+
+		if backslashes % 2 == 0 {
+			return Some(end);
+		}
+		span.start = end;
 	}
 }
 
-fn handle_basic_string_escapes<'a>(text: &Text<'a>, span: Span<'a>) -> Result<CowSpan<'a>, Error> {
-	let mut string = String::with_capacity(span.len());
-
-	let mut chars = span.as_str().char_indices().peekable();
-	while let Some((idx, char)) = chars.next() {
-		let idx = span.start + idx;
-		if char == '\\' {
-			let Some((idx, char)) = chars.next() else {
-				return Err(Error {
-					start: idx,
-					end: idx,
-					kind: ErrorKind::UnknownEscapeSequence,
-				});
-			};
-			let idx = span.start + idx;
-
-			let to_push = match char {
-				'b' => '\u{0008}',
-				't' => '\t',
-				'n' => '\n',
-				'f' => '\u{000C}',
-				'r' => '\r',
-				'"' => '"',
-				'\\' => '\\',
-				'u' => {
-					if idx + 4 > text.end() {
-						return Err(Error {
-							start: idx,
-							end: idx + 4,
-							kind: ErrorKind::UnknownUnicodeScalar,
-						});
+/// Unescapes a basic string that has at least one `\` in it. The text between escapes is
+/// copied in runs, and nothing is copied until the first escape turns out to be valid.
+fn handle_basic_string_escapes<'a>(
+	text: &Text<'a>,
+	span: Span<'a>,
+) -> Result<CowSpan<'a>, Error<'a>> {
+	let source = span.as_str();
+	let bytes = source.as_bytes();
+	let mut string = String::new();
+	// How much of `source` has been handled, and either copied to `string` or skipped
+	let mut copied = 0;
+
+	while let Some(offset) = source[copied..].find('\\') {
+		let relative = copied + offset;
+		let backslash = span.start + relative;
+		let idx = backslash + 1;
+		let Some(char) = source[relative + 1..].chars().next() else {
+			return Err(Error {
+				src: text.excerpt(backslash..=backslash),
+				kind: ErrorKind::UnknownEscapeSequence,
+				section: None,
+			});
+		};
+		// How many bytes the escape takes up, not counting the backslash
+		let mut len = char.len_utf8();
+
+		let to_push = match char {
+			'b' => Some('\u{0008}'),
+			't' => Some('\t'),
+			'n' => Some('\n'),
+			'f' => Some('\u{000C}'),
+			'r' => Some('\r'),
+			'"' => Some('"'),
+			'\\' => Some('\\'),
+			'e' if text.options.toml_version >= TomlVersion::V1_1 => Some('\u{001B}'),
+			'x' if text.options.toml_version >= TomlVersion::V1_1 => {
+				// Exactly 2 hex digits, which are always a valid scalar value
+				let mut end = idx;
+				let mut value = Some(0);
+				let mut digits = source[relative + 2..].chars();
+				for _ in 0..2 {
+					let digit = digits.next();
+					if let Some(digit) = digit {
+						end += digit.len_utf8();
 					}
+					value = value
+						.zip(digit.and_then(|digit| digit.to_digit(16)))
+						.map(|(value, digit)| value * 16 + digit);
+				}
 
-					let source = text.excerpt(idx + 1..=idx + 4);
-					let Some(char) = u32::from_str_radix(source.as_str(), 16)
-						.ok()
-						.and_then(char::from_u32)
-					else {
-						return Err(Error {
-							start: idx,
-							end: idx + 5,
-							kind: ErrorKind::UnknownUnicodeScalar,
-						});
-					};
-
-					chars.nth(3).unwrap();
+				let Some(value) = value else {
+					return Err(Error {
+						src: text.excerpt(backslash..=end),
+						kind: ErrorKind::UnknownUnicodeScalar,
+						section: None,
+					});
+				};
 
-					char
-				}
-				'U' => {
-					if idx + 8 > text.end() {
-						return Err(Error {
-							start: idx,
-							end: idx + 8,
-							kind: ErrorKind::UnknownUnicodeScalar,
-						});
-					}
+				len += 2;
+				Some(char::from(value as u8))
+			}
+			'u' | 'U' => {
+				let digits = if char == 'u' { 4 } else { 8 };
+				let Some(char) = (idx + digits <= text.end())
+					.then(|| text.excerpt(idx + 1..=idx + digits).as_bytes())
+					.and_then(parse_hex)
+					.and_then(char::from_u32)
+				else {
+					return Err(Error {
+						src: text.excerpt(backslash..=idx + digits),
+						kind: ErrorKind::UnknownUnicodeScalar,
+						section: None,
+					});
+				};
 
-					let source = text.excerpt(idx + 1..=idx + 8);
-					let Some(char) = u32::from_str_radix(source.as_str(), 16)
-						.ok()
-						.and_then(char::from_u32)
-					else {
+				len += digits;
+				Some(char)
+			}
+			' ' | '\t' | '\n' | '\r' => {
+				// A line-ending backslash can only have whitespace after it on its own
+				// line. Everything up to the next non-whitespace character on a later line
+				// is trimmed.
+				let rest = &bytes[relative + 1..];
+				match rest
+					.iter()
+					.position(|byte| !matches!(byte, b' ' | b'\t' | b'\r'))
+				{
+					Some(newline) if rest[newline] == b'\n' => {}
+					other => {
+						let end = other.unwrap_or(rest.len() - 1);
 						return Err(Error {
-							start: idx,
-							end: idx + 8,
-							kind: ErrorKind::UnknownUnicodeScalar,
+							src: text.excerpt(backslash..=idx + end),
+							kind: ErrorKind::UnknownEscapeSequence,
+							section: None,
 						});
-					};
-
-					chars.nth(7).unwrap();
-
-					char
-				}
-				' ' | '\t' | '\n' | '\r' => {
-					while let Some((_, char_)) = chars.peek() {
-						let char_ = *char_;
-						if char_ != ' ' && char_ != '\t' && char_ != '\n' && char_ != '\r' {
-							break;
-						}
-						chars.next();
 					}
-					continue;
 				}
-				_ => {
-					return Err(Error {
-						start: span.start + idx,
-						end: span.start + idx + 1,
-						kind: ErrorKind::UnknownEscapeSequence,
-					})
-				}
-			};
 
-			string.push(to_push);
-			continue;
-		}
+				len = rest
+					.iter()
+					.position(|byte| !matches!(byte, b' ' | b'\t' | b'\n' | b'\r'))
+					.unwrap_or(rest.len());
+				None
+			}
+			_ => {
+				return Err(Error {
+					src: text.excerpt(backslash..=idx),
+					kind: ErrorKind::UnknownEscapeSequence,
+					section: None,
+				})
+			}
+		};
 
-		string.push(char);
+		if string.capacity() == 0 {
+			string.reserve(source.len());
+		}
+		string.push_str(&source[copied..relative]);
+		if let Some(char) = to_push {
+			string.push(char);
+		}
+		copied = relative + 1 + len;
 	}
+	string.push_str(&source[copied..]);
 
 	Ok(CowSpan::Modified(span, string))
 }
+
+/// Parses the hex digits in a unicode escape. Unlike [`u32::from_str_radix()`], this
+/// doesn't allow a sign, and it works on bytes, so the digits don't have to be sliced
+/// out of the string first.
+fn parse_hex(digits: &[u8]) -> Option<u32> {
+	digits.iter().try_fold(0_u32, |value, digit| {
+		let digit = (*digit as char).to_digit(16)?;
+		value.checked_mul(16)?.checked_add(digit)
+	})
+}
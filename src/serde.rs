@@ -0,0 +1,423 @@
+//! Deserializes types that implement [`serde::Deserialize`] from TOML. Requires the
+//! `serde` feature.
+//!
+//! ```
+//! use serde::Deserialize;
+//!
+//! #[derive(Deserialize)]
+//! struct Config<'a> {
+//!     name: &'a str,
+//!     server: Server,
+//! }
+//! #[derive(Deserialize)]
+//! struct Server {
+//!     port: u16,
+//!     tls: Option<bool>,
+//! }
+//!
+//! let config: Config = boml::serde::from_str("name = 'boml'\n[server]\nport = 80\n").unwrap();
+//! assert_eq!(config.name, "boml");
+//! assert_eq!(config.server.port, 80);
+//! assert_eq!(config.server.tls, None);
+//! ```
+//!
+//! Strings are borrowed from the source when they don't have escapes, so `&str` fields
+//! work as long as their values don't need any. Enums can be written as a string for
+//! unit variants, or as a table with a single key naming the variant; serde's internally
+//! tagged, adjacently tagged, and untagged enums work too. Date and time values aren't
+//! supported.
+
+use {
+	crate::{crate_prelude::*, OwnedError, Toml},
+	::serde::{
+		de::{
+			self,
+			value::{BorrowedStrDeserializer, StrDeserializer},
+			DeserializeSeed, EnumAccess, IntoDeserializer, MapAccess, SeqAccess, VariantAccess,
+			Visitor,
+		},
+		forward_to_deserialize_any, Deserialize,
+	},
+	std::{
+		collections::hash_map,
+		fmt::{self, Display},
+		slice,
+	},
+};
+
+/// Parses `text` as TOML, then deserializes its root table as a `T`.
+pub fn from_str<'de, T: Deserialize<'de>>(text: &'de str) -> Result<T, Error> {
+	let toml = Toml::parse(text).map_err(|err| Error::Parse(Box::new(err.into_owned())))?;
+	from_table(&toml)
+}
+
+/// Deserializes a `T` from a table.
+pub fn from_table<'de, T: Deserialize<'de>>(table: &Table<'de>) -> Result<T, Error> {
+	T::deserialize(TableDeserializer::new(table))
+}
+
+/// Deserializes a `T` from any TOML value.
+pub fn from_value<'de, T: Deserialize<'de>>(value: &TomlValue<'de>) -> Result<T, Error> {
+	T::deserialize(ValueDeserializer::new(value))
+}
+
+/// Errors while deserializing TOML.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Error {
+	/// The text wasn't valid TOML. Only returned by [`from_str()`].
+	Parse(Box<OwnedError>),
+	/// The TOML didn't match the type being deserialized.
+	Custom {
+		/// What went wrong, from the type being deserialized.
+		message: String,
+		/// The path to the value that caused the error, like `server.ports[1]`. This is
+		/// `None` when the error was caused by the table or value deserialization
+		/// started from.
+		path: Option<String>,
+	},
+}
+impl Error {
+	/// Adds the key or array index that led to the value that caused this error to the
+	/// start of its path.
+	fn in_value(mut self, parent: impl FnOnce() -> String) -> Self {
+		if let Self::Custom { ref mut path, .. } = self {
+			let mut parent = parent();
+			*path = Some(match path.take() {
+				Some(path) if path.starts_with('[') => parent + &path,
+				Some(path) => {
+					parent.push('.');
+					parent + &path
+				}
+				None => parent,
+			});
+		}
+		self
+	}
+}
+impl Display for Error {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			Self::Parse(err) => Display::fmt(err, f),
+			Self::Custom {
+				message,
+				path: Some(path),
+			} => write!(f, "`{path}`: {message}"),
+			Self::Custom {
+				message,
+				path: None,
+			} => f.write_str(message),
+		}
+	}
+}
+impl std::error::Error for Error {
+	fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+		match self {
+			Self::Parse(err) => Some(&**err),
+			Self::Custom { .. } => None,
+		}
+	}
+}
+impl de::Error for Error {
+	fn custom<T: Display>(msg: T) -> Self {
+		Self::Custom {
+			message: msg.to_string(),
+			path: None,
+		}
+	}
+}
+
+/// A [`serde::Deserializer`] for a [`TomlValue`].
+#[derive(Clone, Copy)]
+pub struct ValueDeserializer<'b, 'de> {
+	value: &'b TomlValue<'de>,
+}
+impl<'b, 'de> ValueDeserializer<'b, 'de> {
+	/// Creates a deserializer for `value`.
+	#[inline]
+	pub fn new(value: &'b TomlValue<'de>) -> Self {
+		Self { value }
+	}
+}
+impl<'de> de::Deserializer<'de> for ValueDeserializer<'_, 'de> {
+	type Error = Error;
+
+	fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+		match self.value {
+			TomlValue::String(string) => StringDeserializer(string).deserialize_any(visitor),
+			TomlValue::Integer(int) => visitor.visit_i64(*int),
+			TomlValue::Float(float) => visitor.visit_f64(*float),
+			TomlValue::Boolean(bool) => visitor.visit_bool(*bool),
+			TomlValue::Array(array) => visitor.visit_seq(ArrayAccess {
+				values: array.iter().enumerate(),
+			}),
+			TomlValue::Table(table) => TableDeserializer::new(table).deserialize_any(visitor),
+			TomlValue::OffsetDateTime
+			| TomlValue::LocalDateTime
+			| TomlValue::LocalDate
+			| TomlValue::LocalTime => Err(de::Error::custom(
+				"date and time values aren't supported yet",
+			)),
+		}
+	}
+
+	fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+		// TOML has no null, so missing keys are the only way to write `None`
+		visitor.visit_some(self)
+	}
+
+	fn deserialize_newtype_struct<V: Visitor<'de>>(
+		self,
+		_name: &'static str,
+		visitor: V,
+	) -> Result<V::Value, Error> {
+		visitor.visit_newtype_struct(self)
+	}
+
+	fn deserialize_enum<V: Visitor<'de>>(
+		self,
+		name: &'static str,
+		variants: &'static [&'static str],
+		visitor: V,
+	) -> Result<V::Value, Error> {
+		match self.value {
+			TomlValue::String(string) => {
+				StringDeserializer(string).deserialize_enum(name, variants, visitor)
+			}
+			TomlValue::Table(table) => {
+				TableDeserializer::new(table).deserialize_enum(name, variants, visitor)
+			}
+			_ => Err(de::Error::invalid_type(unexpected(self.value), &visitor)),
+		}
+	}
+
+	fn deserialize_ignored_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+		visitor.visit_unit()
+	}
+
+	forward_to_deserialize_any! {
+		bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+		bytes byte_buf unit unit_struct seq tuple tuple_struct map struct identifier
+	}
+}
+impl<'b, 'de> IntoDeserializer<'de, Error> for &'b TomlValue<'de> {
+	type Deserializer = ValueDeserializer<'b, 'de>;
+
+	fn into_deserializer(self) -> Self::Deserializer {
+		ValueDeserializer::new(self)
+	}
+}
+
+/// A [`serde::Deserializer`] for a [`Table`].
+#[derive(Clone, Copy)]
+pub struct TableDeserializer<'b, 'de> {
+	table: &'b Table<'de>,
+}
+impl<'b, 'de> TableDeserializer<'b, 'de> {
+	/// Creates a deserializer for `table`.
+	#[inline]
+	pub fn new(table: &'b Table<'de>) -> Self {
+		Self { table }
+	}
+}
+impl<'de> de::Deserializer<'de> for TableDeserializer<'_, 'de> {
+	type Error = Error;
+
+	fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+		visitor.visit_map(TableAccess {
+			entries: self.table.map.iter(),
+			value: None,
+		})
+	}
+
+	fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+		visitor.visit_some(self)
+	}
+
+	fn deserialize_newtype_struct<V: Visitor<'de>>(
+		self,
+		_name: &'static str,
+		visitor: V,
+	) -> Result<V::Value, Error> {
+		visitor.visit_newtype_struct(self)
+	}
+
+	/// Externally tagged enums are tables with a single key, which names the variant.
+	fn deserialize_enum<V: Visitor<'de>>(
+		self,
+		_name: &'static str,
+		_variants: &'static [&'static str],
+		visitor: V,
+	) -> Result<V::Value, Error> {
+		let mut entries = self.table.map.iter();
+		match (entries.next(), entries.next()) {
+			(Some((variant, value)), None) => visitor
+				.visit_enum(TableEnum { variant, value })
+				.map_err(|err| err.in_value(|| variant.to_string())),
+			_ => Err(de::Error::invalid_length(
+				self.table.len(),
+				&"a table with exactly one key, naming the enum's variant",
+			)),
+		}
+	}
+
+	fn deserialize_ignored_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+		visitor.visit_unit()
+	}
+
+	forward_to_deserialize_any! {
+		bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+		bytes byte_buf unit unit_struct seq tuple tuple_struct map struct identifier
+	}
+}
+
+/// A [`serde::Deserializer`] for strings and keys, which borrows them from the source
+/// when they don't have escapes.
+struct StringDeserializer<'b, 'de>(&'b CowSpan<'de>);
+impl<'de> de::Deserializer<'de> for StringDeserializer<'_, 'de> {
+	type Error = Error;
+
+	fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+		match self.0 {
+			CowSpan::Raw(span) => visitor.visit_borrowed_str(span.to_str()),
+			CowSpan::Modified(_, string) | CowSpan::Owned(string) => visitor.visit_str(string),
+		}
+	}
+
+	/// Unit variants of enums are written as strings.
+	fn deserialize_enum<V: Visitor<'de>>(
+		self,
+		_name: &'static str,
+		_variants: &'static [&'static str],
+		visitor: V,
+	) -> Result<V::Value, Error> {
+		match self.0 {
+			CowSpan::Raw(span) => visitor.visit_enum(BorrowedStrDeserializer::new(span.to_str())),
+			CowSpan::Modified(_, string) | CowSpan::Owned(string) => {
+				visitor.visit_enum(StrDeserializer::new(string))
+			}
+		}
+	}
+
+	forward_to_deserialize_any! {
+		bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+		bytes byte_buf option unit unit_struct newtype_struct seq tuple tuple_struct
+		map struct identifier ignored_any
+	}
+}
+
+/// Gives the elements of an array to a [`Visitor`].
+struct ArrayAccess<'b, 'de> {
+	values: std::iter::Enumerate<slice::Iter<'b, TomlValue<'de>>>,
+}
+impl<'de> SeqAccess<'de> for ArrayAccess<'_, 'de> {
+	type Error = Error;
+
+	fn next_element_seed<T: DeserializeSeed<'de>>(
+		&mut self,
+		seed: T,
+	) -> Result<Option<T::Value>, Error> {
+		let Some((idx, value)) = self.values.next() else {
+			return Ok(None);
+		};
+		seed.deserialize(ValueDeserializer::new(value))
+			.map(Some)
+			.map_err(|err| err.in_value(|| format!("[{idx}]")))
+	}
+
+	fn size_hint(&self) -> Option<usize> {
+		Some(self.values.len())
+	}
+}
+
+/// Gives the keys and values of a table to a [`Visitor`].
+struct TableAccess<'b, 'de> {
+	entries: hash_map::Iter<'b, CowSpan<'de>, TomlValue<'de>>,
+	/// The key and value of the entry whose key was just visited.
+	value: Option<(&'b CowSpan<'de>, &'b TomlValue<'de>)>,
+}
+impl<'de> MapAccess<'de> for TableAccess<'_, 'de> {
+	type Error = Error;
+
+	fn next_key_seed<K: DeserializeSeed<'de>>(
+		&mut self,
+		seed: K,
+	) -> Result<Option<K::Value>, Error> {
+		let Some((key, value)) = self.entries.next() else {
+			return Ok(None);
+		};
+		self.value = Some((key, value));
+		seed.deserialize(StringDeserializer(key))
+			.map(Some)
+			.map_err(|err| err.in_value(|| key.to_string()))
+	}
+
+	fn next_value_seed<V: DeserializeSeed<'de>>(&mut self, seed: V) -> Result<V::Value, Error> {
+		let (key, value) = self
+			.value
+			.take()
+			.expect("Serde always visits a key before its value");
+		seed.deserialize(ValueDeserializer::new(value))
+			.map_err(|err| err.in_value(|| key.to_string()))
+	}
+
+	fn size_hint(&self) -> Option<usize> {
+		Some(self.entries.len())
+	}
+}
+
+/// An externally tagged enum variant, written as `variant = value`.
+struct TableEnum<'b, 'de> {
+	variant: &'b CowSpan<'de>,
+	value: &'b TomlValue<'de>,
+}
+impl<'b, 'de> EnumAccess<'de> for TableEnum<'b, 'de> {
+	type Error = Error;
+	type Variant = ValueDeserializer<'b, 'de>;
+
+	fn variant_seed<V: DeserializeSeed<'de>>(
+		self,
+		seed: V,
+	) -> Result<(V::Value, Self::Variant), Error> {
+		let variant = seed.deserialize(StringDeserializer(self.variant))?;
+		Ok((variant, ValueDeserializer::new(self.value)))
+	}
+}
+impl<'de> VariantAccess<'de> for ValueDeserializer<'_, 'de> {
+	type Error = Error;
+
+	fn unit_variant(self) -> Result<(), Error> {
+		Err(de::Error::invalid_type(
+			unexpected(self.value),
+			&"a string naming the variant",
+		))
+	}
+
+	fn newtype_variant_seed<T: DeserializeSeed<'de>>(self, seed: T) -> Result<T::Value, Error> {
+		seed.deserialize(self)
+	}
+
+	fn tuple_variant<V: Visitor<'de>>(self, _len: usize, visitor: V) -> Result<V::Value, Error> {
+		de::Deserializer::deserialize_seq(self, visitor)
+	}
+
+	fn struct_variant<V: Visitor<'de>>(
+		self,
+		_fields: &'static [&'static str],
+		visitor: V,
+	) -> Result<V::Value, Error> {
+		de::Deserializer::deserialize_map(self, visitor)
+	}
+}
+
+/// Describes a value for serde's type errors.
+fn unexpected<'b>(value: &'b TomlValue<'_>) -> de::Unexpected<'b> {
+	match value {
+		TomlValue::String(string) => de::Unexpected::Str(string.as_str()),
+		TomlValue::Integer(int) => de::Unexpected::Signed(*int),
+		TomlValue::Float(float) => de::Unexpected::Float(*float),
+		TomlValue::Boolean(bool) => de::Unexpected::Bool(*bool),
+		TomlValue::Array(_) => de::Unexpected::Seq,
+		TomlValue::Table(_) => de::Unexpected::Map,
+		_ => de::Unexpected::Other("a date or time"),
+	}
+}
@@ -0,0 +1,454 @@
+//! Serializers for writing values back out as TOML text.
+
+use {
+	crate::{
+		crate_prelude::*,
+		document::{self, write_dotted_key},
+		text::is_bare_key_byte,
+	},
+	std::{
+		collections::HashMap,
+		fmt::{self, Display, Write},
+		io,
+	},
+};
+
+/// How strings should be written when they're serialized as TOML.
+///
+/// Every style produces text that parses back to the exact same string; they only differ
+/// in how that text looks.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum EscapeStyle {
+	/// Prefer literal strings (`'...'`) when the string has no quotes or control
+	/// characters, and fall back to basic strings (`"..."`) with as few escapes as
+	/// possible otherwise. Strings containing newlines are written as multiline strings.
+	#[default]
+	Minimal,
+	/// Always write single-line basic strings (`"..."`), escaping only what TOML
+	/// requires. Newlines are written as `\n`.
+	AlwaysBasic,
+	/// Like [`EscapeStyle::AlwaysBasic`], but every non-ASCII character is also escaped,
+	/// as `\uXXXX` or `\UXXXXXXXX`. The output is pure ASCII.
+	AsciiOnly,
+}
+
+/// Writes `string` as a TOML string, using the provided [`EscapeStyle`].
+pub fn write_string<W: Write>(out: &mut W, string: &str, style: EscapeStyle) -> fmt::Result {
+	match style {
+		EscapeStyle::Minimal => {
+			if string.contains('\n') {
+				if is_literal_safe(string, true) {
+					// The first newline after the opening delimiter gets trimmed by parsers,
+					// so it's always written and the string's own newlines are left intact.
+					write!(out, "'''\n{string}'''")
+				} else {
+					out.write_str("\"\"\"\n")?;
+					write_basic_string_body(out, string, true, false)?;
+					out.write_str("\"\"\"")
+				}
+			} else if is_literal_safe(string, false) {
+				write!(out, "'{string}'")
+			} else {
+				out.write_char('"')?;
+				write_basic_string_body(out, string, false, false)?;
+				out.write_char('"')
+			}
+		}
+		EscapeStyle::AlwaysBasic | EscapeStyle::AsciiOnly => {
+			out.write_char('"')?;
+			write_basic_string_body(out, string, false, style == EscapeStyle::AsciiOnly)?;
+			out.write_char('"')
+		}
+	}
+}
+
+/// Identical to [`write_string`], but returns the result as a new [`String`].
+pub fn string_to_toml(string: &str, style: EscapeStyle) -> String {
+	let mut out = String::with_capacity(string.len() + 2);
+	// Writing to a `String` can't fail
+	write_string(&mut out, string, style).unwrap();
+	out
+}
+
+/// Writes a single key (not a dotted key). Keys are written bare if possible, and quoted
+/// otherwise.
+pub fn write_key<W: Write>(out: &mut W, key: &str) -> fmt::Result {
+	if !key.is_empty() && key.bytes().all(is_bare_key_byte) {
+		out.write_str(key)
+	} else if is_literal_safe(key, false) {
+		write!(out, "'{key}'")
+	} else {
+		out.write_char('"')?;
+		write_basic_string_body(out, key, false, false)?;
+		out.write_char('"')
+	}
+}
+
+/// Writes a value as TOML. Tables are written as inline tables, and strings are written
+/// with the provided [`EscapeStyle`].
+///
+/// Time values are currently unsupported, and will cause this to return an error.
+pub fn write_value<W: Write>(
+	out: &mut W,
+	value: &TomlValue<'_>,
+	style: EscapeStyle,
+) -> fmt::Result {
+	match value {
+		TomlValue::String(string) => write_string(out, string.as_str(), style),
+		TomlValue::Integer(int) => write!(out, "{int}"),
+		TomlValue::Float(float) => {
+			let sign = if float.is_sign_negative() { "-" } else { "" };
+			if float.is_nan() {
+				write!(out, "{sign}nan")
+			} else if float.is_infinite() {
+				write!(out, "{sign}inf")
+			} else {
+				// Debug formatting always includes a `.` or exponent, so the float won't be
+				// read back as an integer
+				write!(out, "{float:?}")
+			}
+		}
+		TomlValue::Boolean(bool_) => write!(out, "{bool_}"),
+		TomlValue::Array(array) => {
+			out.write_char('[')?;
+			for (idx, value) in array.iter().enumerate() {
+				if idx != 0 {
+					out.write_str(", ")?;
+				}
+				write_value(out, value, style)?;
+			}
+			out.write_char(']')
+		}
+		TomlValue::Table(table) => {
+			if table.is_empty() {
+				return out.write_str("{}");
+			}
+
+			out.write_str("{ ")?;
+			for (idx, (key, value)) in table.iter().enumerate() {
+				if idx != 0 {
+					out.write_str(", ")?;
+				}
+				write_key(out, key)?;
+				out.write_str(" = ")?;
+				write_value(out, value, style)?;
+			}
+			out.write_str(" }")
+		}
+		TomlValue::OffsetDateTime
+		| TomlValue::LocalDateTime
+		| TomlValue::LocalDate
+		| TomlValue::LocalTime => Err(fmt::Error),
+	}
+}
+
+/// Checks if `string` can be written as a literal string without changing its meaning.
+/// Literal strings can't have escapes, so they can't contain their own delimiter or any
+/// control characters besides tabs (and newlines, for multiline literal strings).
+fn is_literal_safe(string: &str, multiline: bool) -> bool {
+	if multiline {
+		// Quotes at the very end would merge with the closing delimiter
+		if string.contains("'''") || string.ends_with('\'') {
+			return false;
+		}
+	} else if string.contains('\'') {
+		return false;
+	}
+
+	string.chars().all(|char_| match char_ {
+		'\t' => true,
+		'\n' => multiline,
+		_ => !is_control(char_),
+	})
+}
+
+/// Writes the contents of a basic string, without its delimiters. In multiline strings,
+/// newlines are written as-is instead of being escaped.
+fn write_basic_string_body<W: Write>(
+	out: &mut W,
+	string: &str,
+	multiline: bool,
+	ascii_only: bool,
+) -> fmt::Result {
+	for char_ in string.chars() {
+		match char_ {
+			// Quotes are always escaped, which also prevents `"""` from appearing in
+			// multiline strings.
+			'"' => out.write_str("\\\"")?,
+			'\\' => out.write_str("\\\\")?,
+			'\n' if multiline => out.write_char('\n')?,
+			'\n' => out.write_str("\\n")?,
+			'\t' => out.write_str("\\t")?,
+			'\r' => out.write_str("\\r")?,
+			'\u{0008}' => out.write_str("\\b")?,
+			'\u{000C}' => out.write_str("\\f")?,
+			_ if is_control(char_) => write!(out, "\\u{:04X}", char_ as u32)?,
+			_ if ascii_only && !char_.is_ascii() => {
+				if (char_ as u32) <= 0xFFFF {
+					write!(out, "\\u{:04X}", char_ as u32)?
+				} else {
+					write!(out, "\\U{:08X}", char_ as u32)?
+				}
+			}
+			_ => out.write_char(char_)?,
+		}
+	}
+
+	Ok(())
+}
+
+/// Control characters that TOML doesn't allow unescaped in strings. Tabs are allowed,
+/// and newlines are handled separately since multiline strings allow them.
+#[inline]
+fn is_control(char_: char) -> bool {
+	matches!(char_, '\u{0000}'..='\u{0008}' | '\u{000A}'..='\u{001F}' | '\u{007F}')
+}
+
+/// Writes a TOML document one section at a time, straight to an [`io::Write`], so large
+/// documents never have to be built in memory. Values are formatted exactly like
+/// [`write_value()`] formats them, with [`EscapeStyle::Minimal`].
+///
+/// The writer checks TOML's structural rules as it goes - for example, that no table is
+/// defined twice, and that a key isn't used twice in one section - and returns a
+/// [`WriteError`] instead of writing anything that would make the document invalid.
+///
+/// ```rust
+/// use boml::prelude::*;
+///
+/// let mut writer = TomlWriter::new(Vec::new());
+/// writer.key_value("title", "example").unwrap();
+/// writer.begin_table("server").unwrap();
+/// writer.key_value("port", 8080).unwrap();
+/// writer.end().unwrap();
+/// for name in ["alice", "bob"] {
+///     writer.begin_array_of_tables("users").unwrap();
+///     writer.key_value("name", name).unwrap();
+///     writer.end().unwrap();
+/// }
+///
+/// let output = String::from_utf8(writer.into_inner()).unwrap();
+/// assert_eq!(
+///     output,
+///     "title = 'example'\n\n[server]\nport = 8080\n\n[[users]]\nname = 'alice'\n\n[[users]]\nname = 'bob'\n"
+/// );
+/// assert!(Toml::parse(&output).is_ok());
+/// ```
+pub struct TomlWriter<W: io::Write> {
+	out: W,
+	/// Everything that's been defined in the document so far.
+	root: WrittenNode,
+	/// The path of the section keys are currently written to, or `None` if the last
+	/// section was closed with [`TomlWriter::end()`]. The root table is an empty path.
+	section: Option<Vec<String>>,
+	/// If anything has been written yet, so sections can be separated with blank lines.
+	written: bool,
+	/// A reusable buffer for formatting each line before it's written.
+	line: String,
+}
+impl<W: io::Write> TomlWriter<W> {
+	/// Creates a writer that writes to `out`. Keys written before the first section go in
+	/// the root table.
+	pub fn new(out: W) -> Self {
+		Self {
+			out,
+			root: WrittenNode::default(),
+			section: Some(Vec::new()),
+			written: false,
+			line: String::new(),
+		}
+	}
+
+	/// Starts a `[table]` section at `path`, which is a (possibly dotted) TOML key like
+	/// `package.metadata`. Keys written after this go in that table.
+	pub fn begin_table(&mut self, path: &str) -> Result<(), WriteError> {
+		self.begin_section(path, false)
+	}
+
+	/// Starts a `[[table]]` section at `path`, adding a new table to the end of that array
+	/// of tables. Keys written after this go in the new table.
+	pub fn begin_array_of_tables(&mut self, path: &str) -> Result<(), WriteError> {
+		self.begin_section(path, true)
+	}
+
+	/// Writes a `key = value` pair in the current section. `key` is a single key, not a
+	/// dotted key; it's quoted if it has to be.
+	pub fn key_value<'v>(
+		&mut self,
+		key: &str,
+		value: impl Into<TomlValue<'v>>,
+	) -> Result<(), WriteError> {
+		let Some(ref section) = self.section else {
+			return Err(WriteError::NoSection);
+		};
+		let node = self.root.get_mut(section);
+		if node.children.contains_key(key) {
+			return Err(WriteError::DuplicateKey);
+		}
+
+		self.line.clear();
+		write_key(&mut self.line, key).unwrap();
+		self.line.push_str(" = ");
+		write_value(&mut self.line, &value.into(), EscapeStyle::Minimal)
+			.map_err(|_| WriteError::UnsupportedValue)?;
+		self.line.push('\n');
+		self.out.write_all(self.line.as_bytes())?;
+
+		node.children
+			.insert(key.to_string(), WrittenNode::new(NodeKind::Value));
+		self.written = true;
+		Ok(())
+	}
+
+	/// Closes the current section. Another section has to be started before any more keys
+	/// can be written, since TOML has no way to return to a table after its section ends.
+	pub fn end(&mut self) -> Result<(), WriteError> {
+		match self.section.take() {
+			Some(_) => Ok(()),
+			None => Err(WriteError::NoSection),
+		}
+	}
+
+	/// Consumes the writer, returning the [`io::Write`] it was writing to.
+	pub fn into_inner(self) -> W {
+		self.out
+	}
+
+	fn begin_section(&mut self, path: &str, array: bool) -> Result<(), WriteError> {
+		let path = document::parse_path(path).map_err(|_| WriteError::InvalidPath)?;
+		let (last, parents) = path.split_last().unwrap();
+
+		// Check the whole path before changing anything, so a failed call has no effect
+		let mut node = Some(&self.root);
+		for key in parents {
+			node = node.and_then(|node| node.children.get(key));
+			if node.is_some_and(|node| node.kind == NodeKind::Value) {
+				return Err(WriteError::NotATable);
+			}
+		}
+		let valid = match node.and_then(|node| node.children.get(last)) {
+			None => true,
+			Some(node) => match node.kind {
+				NodeKind::Implicit => !array,
+				NodeKind::Table => false,
+				NodeKind::ArrayOfTables => array,
+				NodeKind::Value => return Err(WriteError::DuplicateKey),
+			},
+		};
+		if !valid {
+			return Err(WriteError::DuplicateTable);
+		}
+
+		self.line.clear();
+		if self.written {
+			self.line.push('\n');
+		}
+		self.line.push_str(if array { "[[" } else { "[" });
+		write_dotted_key(&mut self.line, &path).unwrap();
+		self.line.push_str(if array { "]]\n" } else { "]\n" });
+		self.out.write_all(self.line.as_bytes())?;
+
+		let mut node = &mut self.root;
+		for key in parents {
+			node = node.children.entry(key.clone()).or_default();
+		}
+		let node = node.children.entry(last.clone()).or_default();
+		if array {
+			// Only the newest table in an array of tables can be added to
+			node.kind = NodeKind::ArrayOfTables;
+			node.children.clear();
+		} else {
+			node.kind = NodeKind::Table;
+		}
+
+		self.section = Some(path);
+		self.written = true;
+		Ok(())
+	}
+}
+
+/// Errors from a [`TomlWriter`]. Each one names the rule that writing would have broken;
+/// nothing is written when an error is returned.
+#[derive(Debug)]
+pub enum WriteError {
+	/// Writing to the underlying [`io::Write`] failed.
+	Io(io::Error),
+	/// The path couldn't be parsed as a TOML key.
+	InvalidPath,
+	/// A table with this path was already defined, or the path was already used for an
+	/// array of tables (or vice versa).
+	DuplicateTable,
+	/// The key was already defined in this section.
+	DuplicateKey,
+	/// A part of the path is a value that isn't a table, so it can't have sub-tables.
+	NotATable,
+	/// The value can't be written as TOML. Time values are currently unsupported.
+	UnsupportedValue,
+	/// A key was written, or a section was closed, after [`TomlWriter::end()`] without
+	/// starting a new section.
+	NoSection,
+}
+impl Display for WriteError {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			Self::Io(err) => write!(f, "failed to write TOML: {err}"),
+			Self::InvalidPath => f.write_str("the path isn't a valid TOML key"),
+			Self::DuplicateTable => f.write_str("tables can't be defined more than once"),
+			Self::DuplicateKey => f.write_str("keys can't be defined more than once"),
+			Self::NotATable => f.write_str("only tables can have sub-tables"),
+			Self::UnsupportedValue => f.write_str("the value can't be written as TOML"),
+			Self::NoSection => {
+				f.write_str("keys can't be written after a section ends, until another starts")
+			}
+		}
+	}
+}
+impl std::error::Error for WriteError {
+	fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+		match self {
+			Self::Io(err) => Some(err),
+			_ => None,
+		}
+	}
+}
+impl From<io::Error> for WriteError {
+	fn from(err: io::Error) -> Self {
+		Self::Io(err)
+	}
+}
+
+/// A key a [`TomlWriter`] has written, and the keys under it.
+#[derive(Default)]
+struct WrittenNode {
+	kind: NodeKind,
+	/// The keys in this table. For arrays of tables, these are the keys in the newest
+	/// table, since the older ones can't be added to anymore.
+	children: HashMap<String, WrittenNode>,
+}
+impl WrittenNode {
+	fn new(kind: NodeKind) -> Self {
+		Self {
+			kind,
+			children: HashMap::new(),
+		}
+	}
+
+	/// Gets the node at `path`, which has to exist.
+	fn get_mut(&mut self, path: &[String]) -> &mut Self {
+		path.iter()
+			.fold(self, |node, key| node.children.get_mut(key).unwrap())
+	}
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+enum NodeKind {
+	/// A table that was created because a header named one of its sub-tables.
+	#[default]
+	Implicit,
+	/// A table defined by a `[table]` header.
+	Table,
+	/// An array of tables, from `[[table]]` headers.
+	ArrayOfTables,
+	/// A value from a `key = value` pair. Inline tables and arrays can't be extended, so
+	/// they count as values, too.
+	Value,
+}
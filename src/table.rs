@@ -1,16 +1,183 @@
 //! Defines the [`Table`] type.
 
 use {
-	crate::crate_prelude::*,
-	std::{collections::HashMap, ops::Deref},
+	crate::{crate_prelude::*, document},
+	std::{
+		collections::{
+			hash_map::{DefaultHasher, Entry, RandomState},
+			HashMap,
+		},
+		fmt::{self, Debug, Display},
+		hash::{BuildHasher, Hasher},
+		ops::Deref,
+	},
 };
 
+/// The hasher [`Table`]s use for their keys. By default this is the standard library's
+/// [`RandomState`], which is resistant to HashDoS attacks. Parsing with
+/// [`ParseOptions::fast_hash()`] switches the document's tables to [`FastHasher`]
+/// instead.
+///
+/// [`RandomState`]: std::collections::hash_map::RandomState
+/// [`ParseOptions::fast_hash()`]: crate::options::ParseOptions::fast_hash()
+#[derive(Debug, Clone, Default)]
+pub struct TableHasher(HasherKind);
+#[derive(Debug, Clone)]
+enum HasherKind {
+	Sip(RandomState),
+	Fast,
+}
+impl Default for HasherKind {
+	fn default() -> Self {
+		Self::Sip(RandomState::new())
+	}
+}
+impl TableHasher {
+	/// A hasher that uses [`FastHasher`]. See its docs for when that's a good idea.
+	#[inline]
+	pub fn fast() -> Self {
+		Self(HasherKind::Fast)
+	}
+
+	/// If this hashes with [`FastHasher`].
+	#[inline]
+	pub fn is_fast(&self) -> bool {
+		matches!(self.0, HasherKind::Fast)
+	}
+}
+impl BuildHasher for TableHasher {
+	type Hasher = TableKeyHasher;
+
+	#[inline]
+	fn build_hasher(&self) -> Self::Hasher {
+		TableKeyHasher(match &self.0 {
+			HasherKind::Sip(state) => KeyHasherKind::Sip(state.build_hasher()),
+			HasherKind::Fast => KeyHasherKind::Fast(FastHasher::default()),
+		})
+	}
+}
+
+/// The [`Hasher`] a [`TableHasher`] builds.
+#[derive(Debug, Clone)]
+pub struct TableKeyHasher(KeyHasherKind);
+#[derive(Debug, Clone)]
+enum KeyHasherKind {
+	Sip(DefaultHasher),
+	Fast(FastHasher),
+}
+impl Hasher for TableKeyHasher {
+	#[inline]
+	fn write(&mut self, bytes: &[u8]) {
+		match &mut self.0 {
+			KeyHasherKind::Sip(hasher) => hasher.write(bytes),
+			KeyHasherKind::Fast(hasher) => hasher.write(bytes),
+		}
+	}
+
+	#[inline]
+	fn write_u8(&mut self, int: u8) {
+		match &mut self.0 {
+			KeyHasherKind::Sip(hasher) => hasher.write_u8(int),
+			KeyHasherKind::Fast(hasher) => hasher.write_u8(int),
+		}
+	}
+
+	#[inline]
+	fn write_usize(&mut self, int: usize) {
+		match &mut self.0 {
+			KeyHasherKind::Sip(hasher) => hasher.write_usize(int),
+			KeyHasherKind::Fast(hasher) => hasher.write_usize(int),
+		}
+	}
+
+	#[inline]
+	fn finish(&self) -> u64 {
+		match &self.0 {
+			KeyHasherKind::Sip(hasher) => hasher.finish(),
+			KeyHasherKind::Fast(hasher) => hasher.finish(),
+		}
+	}
+}
+
+/// A fast, non-cryptographic hasher, in the style of rustc's FxHash. Keys in TOML
+/// documents are usually short, and hashing them with the standard library's SipHash
+/// can show up in profiles of documents with thousands of small tables.
+///
+/// Unlike SipHash, this isn't resistant to HashDoS attacks: someone who controls a
+/// document's keys can pick keys that collide, and make parsing slow. It's only used
+/// for [`Table`]s parsed with [`ParseOptions::fast_hash()`], which should only be done
+/// for trusted documents.
+///
+/// [`ParseOptions::fast_hash()`]: crate::options::ParseOptions::fast_hash()
+#[derive(Debug, Clone, Copy, Default)]
+pub struct FastHasher {
+	hash: u64,
+}
+impl FastHasher {
+	const SEED: u64 = 0x51_7c_c1_b7_27_22_0a_95;
+
+	#[inline(always)]
+	fn add(&mut self, word: u64) {
+		self.hash = (self.hash.rotate_left(5) ^ word).wrapping_mul(Self::SEED);
+	}
+}
+impl Hasher for FastHasher {
+	#[inline]
+	fn write(&mut self, mut bytes: &[u8]) {
+		while let Some((word, rest)) = bytes.split_first_chunk::<8>() {
+			self.add(u64::from_le_bytes(*word));
+			bytes = rest;
+		}
+		if let Some((word, rest)) = bytes.split_first_chunk::<4>() {
+			self.add(u32::from_le_bytes(*word) as u64);
+			bytes = rest;
+		}
+		for byte in bytes {
+			self.add(*byte as u64);
+		}
+	}
+
+	#[inline]
+	fn write_u8(&mut self, int: u8) {
+		self.add(int as u64);
+	}
+
+	#[inline]
+	fn write_usize(&mut self, int: usize) {
+		self.add(int as u64);
+	}
+
+	#[inline]
+	fn finish(&self) -> u64 {
+		self.hash
+	}
+}
+
 /// A set of key/value pairs in TOML.
-#[derive(Debug, PartialEq, Default)]
+///
+/// Cloning a table clones all of its values, but they still borrow from the same
+/// source, so the clone keeps the same key and header spans.
+#[derive(Default, Clone)]
 pub struct Table<'a> {
-	pub(crate) map: HashMap<CowSpan<'a>, TomlValue<'a>>,
+	pub(crate) map: HashMap<CowSpan<'a>, TomlValue<'a>, TableHasher>,
+	/// How the table was defined, which decides whether it can be extended later in the
+	/// document. This isn't part of the table's value, so it's ignored when comparing
+	/// tables.
+	pub(crate) definition: Definition,
+	/// The `[table]` or `[[table]]` header that defined the table, if it has one.
+	pub(crate) header: Option<Span<'a>>,
 }
 impl<'a> Table<'a> {
+	/// Creates an empty table with room for `capacity` keys, which hashes them with
+	/// `hasher`.
+	pub(crate) fn with_hasher(capacity: usize, hasher: TableHasher) -> Self {
+		Self {
+			map: HashMap::with_capacity_and_hasher(capacity, hasher),
+			definition: Definition::default(),
+			header: None,
+		}
+	}
+
 	/// Gets the value for a key, if that value is a table.
 	pub fn get_table(&self, key: &str) -> Result<&Self, TomlGetError<'_, 'a>> {
 		match self.get(key) {
@@ -77,7 +244,7 @@ impl<'a> Table<'a> {
 		}
 	}
 	/// Gets the value for a key, if that value is an array.
-	pub fn get_array(&self, key: &str) -> Result<&Vec<TomlValue<'a>>, TomlGetError<'_, 'a>> {
+	pub fn get_array(&self, key: &str) -> Result<&[TomlValue<'a>], TomlGetError<'_, 'a>> {
 		match self.get(key) {
 			None => Err(TomlGetError::InvalidKey),
 			Some(ref val) => {
@@ -90,58 +257,146 @@ impl<'a> Table<'a> {
 		}
 	}
 
-	/// Inserts a value into the table, handling dotted keys automatically. Returns true if
-	/// inserting the value overwrote another value.
-	pub(crate) fn insert(&mut self, key: Key<'a>, value: TomlValue<'a>) -> bool {
-		if let Some(child) = key.child {
-			let possible_table = self
-				.map
-				.entry(key.text)
-				.or_insert(TomlValue::Table(Table::default()));
-
-			let table = match possible_table {
-				TomlValue::Array(array) => {
-					let Some(TomlValue::Table(table)) = array.last_mut() else {
-						return true;
-					};
-					table
+	/// Gets the value at `path`, which is a (possibly dotted) TOML key like
+	/// `package.version`. Each part of the path before the last has to be a table.
+	///
+	/// If there's no value at the path, or the path isn't a valid TOML key, this returns
+	/// [`TomlGetError::InvalidKey`]. If a value along the path isn't a table, this returns
+	/// a [`TomlGetError::TypeMismatch`] with that value.
+	pub fn get_path(&self, path: &str) -> Result<&TomlValue<'a>, TomlGetError<'_, 'a>> {
+		let path = document::parse_path(path).map_err(|_| TomlGetError::InvalidKey)?;
+		let (last, parents) = path.split_last().unwrap();
+
+		let mut table = self;
+		for key in parents {
+			match table.get(key.as_str()) {
+				None => return Err(TomlGetError::InvalidKey),
+				Some(TomlValue::Table(child)) => table = child,
+				Some(other) => return Err(TomlGetError::TypeMismatch(other, other.value_type())),
+			}
+		}
+
+		table.get(last.as_str()).ok_or(TomlGetError::InvalidKey)
+	}
+
+	/// Gets many values at once, for when either all of them are needed, or a complete
+	/// list of what's wrong. Each request is a path (see [`Table::get_path()`]) and the
+	/// type the value there should have.
+	///
+	/// If every value is found and has the right type, they're returned in the same
+	/// order as the requests. Otherwise, every request that failed is returned with its
+	/// path and the reason it failed, in the same order as the requests.
+	///
+	/// ```rust
+	/// use boml::prelude::*;
+	///
+	/// let toml = Toml::parse("name = 'app'\n[plugins.auth]\nenabled = true\n").unwrap();
+	/// let values = toml
+	///     .get_many(&[
+	///         ("name", TomlValueType::String),
+	///         ("plugins.auth.enabled", TomlValueType::Boolean),
+	///     ])
+	///     .unwrap();
+	/// assert_eq!(values[1], &TomlValue::Boolean(true));
+	///
+	/// let errors = toml
+	///     .get_many(&[
+	///         ("name", TomlValueType::Integer),
+	///         ("plugins.cache.enabled", TomlValueType::Boolean),
+	///     ])
+	///     .unwrap_err();
+	/// assert_eq!(errors[1], ("plugins.cache.enabled", TomlGetError::InvalidKey));
+	/// ```
+	pub fn get_many<'p>(
+		&self,
+		requests: &[(&'p str, TomlValueType)],
+	) -> Result<Vec<&TomlValue<'a>>, Vec<(&'p str, TomlGetError<'_, 'a>)>> {
+		let mut values = Vec::with_capacity(requests.len());
+		let mut errors = Vec::new();
+
+		for (path, ty) in requests {
+			match self.get_path(path) {
+				Ok(value) if value.value_type() == *ty => values.push(value),
+				Ok(value) => {
+					errors.push((*path, TomlGetError::TypeMismatch(value, value.value_type())))
 				}
-				TomlValue::Table(table) => table,
-				_ => return true,
-			};
+				Err(err) => errors.push((*path, err)),
+			}
+		}
 
-			table.insert(*child, value)
+		if errors.is_empty() {
+			Ok(values)
 		} else {
-			self.map.insert(key.text, value).is_some()
+			Err(errors)
 		}
 	}
-	/// Gets a value from the table, or inserts one if it doesn't exist. This handles dotted keys automatically,
-	/// but will return `None` if the key is invalid (ie indexes into something that isn't a table).
-	pub(crate) fn get_or_insert_mut(
-		&mut self,
-		key: Key<'a>,
-		value: TomlValue<'a>,
-	) -> Option<&mut TomlValue<'a>> {
+
+	/// Gets the value for a key, if there is one and it's a table. Unlike
+	/// [`Table::get_table()`], this doesn't say why there's no table, which is simpler when
+	/// a missing value is fine.
+	pub fn try_get_table(&self, key: &str) -> Option<&Self> {
+		self.get(key).and_then(TomlValue::table)
+	}
+	/// Gets the value for a key, if there is one and it's a string. See
+	/// [`Table::try_get_table()`].
+	pub fn try_get_string(&self, key: &str) -> Option<&str> {
+		self.get(key).and_then(TomlValue::string)
+	}
+	/// Gets the value for a key, if there is one and it's an integer. See
+	/// [`Table::try_get_table()`].
+	pub fn try_get_integer(&self, key: &str) -> Option<i64> {
+		self.get(key).and_then(TomlValue::integer)
+	}
+	/// Gets the value for a key, if there is one and it's a float. See
+	/// [`Table::try_get_table()`].
+	pub fn try_get_float(&self, key: &str) -> Option<f64> {
+		self.get(key).and_then(TomlValue::float)
+	}
+	/// Gets the value for a key, if there is one and it's a boolean. See
+	/// [`Table::try_get_table()`].
+	pub fn try_get_boolean(&self, key: &str) -> Option<bool> {
+		self.get(key).and_then(TomlValue::boolean)
+	}
+	/// Gets the value for a key, if there is one and it's an array. See
+	/// [`Table::try_get_table()`].
+	pub fn try_get_array(&self, key: &str) -> Option<&[TomlValue<'a>]> {
+		self.get(key).and_then(TomlValue::array)
+	}
+
+	/// Inserts a value into the table, handling dotted keys automatically. Dotted keys can
+	/// only add to tables that were also created by dotted keys; if the key is already
+	/// defined, or a part of it names any other value, this returns a
+	/// [`ErrorKind::ReusedKey`] error for that part, and the table isn't changed.
+	pub(crate) fn insert(&mut self, key: Key<'a>, value: TomlValue<'a>) -> Result<(), Error<'a>> {
+		let err = Error {
+			src: key.span(),
+			kind: ErrorKind::ReusedKey,
+			section: None,
+		};
+
 		if let Some(child) = key.child {
-			let possible_table = self
-				.map
-				.entry(key.text)
-				.or_insert(TomlValue::Table(Table::default()));
-
-			let table = match possible_table {
-				TomlValue::Array(array) => {
-					let Some(TomlValue::Table(table)) = array.last_mut() else {
-						return None;
-					};
-					table
-				}
-				TomlValue::Table(table) => table,
-				_ => return None,
-			};
+			let hasher = self.map.hasher().clone();
+			let possible_table = self.map.entry(key.text).or_insert_with(|| {
+				TomlValue::Table(Table {
+					definition: Definition::Dotted,
+					..Table::with_hasher(0, hasher)
+				})
+			});
 
-			table.get_or_insert_mut(*child, value)
+			match possible_table {
+				TomlValue::Table(table) if table.definition == Definition::Dotted => {
+					table.insert(*child, value)
+				}
+				_ => Err(err),
+			}
 		} else {
-			Some(self.map.entry(key.text).or_insert(value))
+			match self.map.entry(key.text) {
+				Entry::Occupied(_) => Err(err),
+				Entry::Vacant(entry) => {
+					entry.insert(value);
+					Ok(())
+				}
+			}
 		}
 	}
 
@@ -150,15 +405,92 @@ impl<'a> Table<'a> {
 	pub fn iter(&self) -> impl Iterator<Item = (&str, &TomlValue<'_>)> {
 		self.map.iter().map(|(k, v)| (k.as_str(), v))
 	}
+	/// Like [`Table::iter()`], but also gives where each key was written in the source. See
+	/// [`Table::key_span()`].
+	pub fn entries_with_spans(
+		&self,
+	) -> impl Iterator<Item = (&str, Option<Span<'a>>, &TomlValue<'a>)> {
+		self.map
+			.iter()
+			.map(|(key, value)| (key.as_str(), key.source_span().copied(), value))
+	}
+
+	/// Finds where `key` was written in the source, so errors about the key can point at
+	/// it instead of its value. Returns `None` if the table doesn't have the key, or if
+	/// the key wasn't parsed from a source, like keys converted from the `toml` crate.
+	///
+	/// Quoted keys' spans don't include the quotes. Tables can be named more than once,
+	/// like `a` in `[a.b]` and `[a.c]`, or in `a.b = 1` and `a.c = 2`; their span is where
+	/// they were first named.
+	pub fn key_span(&self, key: &str) -> Option<Span<'a>> {
+		self.map
+			.get_key_value(key)
+			.and_then(|(key, _)| key.source_span().copied())
+	}
+
+	/// The entire `[table]` or `[[table]]` header that defined this table, including the
+	/// brackets, so errors about the whole table can point at it. Each table in an array
+	/// of tables has its own header.
+	///
+	/// Returns `None` for tables that weren't defined by a header: the root table, inline
+	/// tables, tables created by dotted keys, and tables that were only named as part of
+	/// another header, like `a` in `[a.b]`.
+	///
+	/// ```
+	/// use boml::prelude::*;
+	///
+	/// let toml = Toml::parse("[server]\nport = 80\n\n[[user]]\n[[user]]\n").unwrap();
+	/// let server = toml.get_table("server").unwrap();
+	/// assert_eq!(server.header_span().unwrap().as_str(), "[server]");
+	/// assert_eq!(server.header_span().unwrap().line_col(), (1, 1));
+	///
+	/// let users = toml.get_array("user").unwrap();
+	/// let second = users[1].table().unwrap();
+	/// assert_eq!(second.header_span().unwrap().line_col(), (5, 1));
+	/// ```
+	#[inline]
+	pub fn header_span(&self) -> Option<Span<'a>> {
+		self.header
+	}
+}
+impl Debug for Table<'_> {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.debug_struct("Table").field("map", &self.map).finish()
+	}
+}
+impl PartialEq for Table<'_> {
+	fn eq(&self, other: &Self) -> bool {
+		self.map == other.map
+	}
 }
 impl<'a> Deref for Table<'a> {
-	type Target = HashMap<CowSpan<'a>, TomlValue<'a>>;
+	type Target = HashMap<CowSpan<'a>, TomlValue<'a>, TableHasher>;
 
 	fn deref(&self) -> &Self::Target {
 		&self.map
 	}
 }
 
+/// How a [`Table`] was defined in a TOML document. TOML only lets each table be defined
+/// once, so this is used to reject documents that define the same table twice, or extend
+/// a table in a way the spec forbids.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub(crate) enum Definition {
+	/// The table was created because a header named one of its sub-tables, like `a` in
+	/// `[a.b]`, or it wasn't parsed from a document at all. A later `[a]` header can still
+	/// define it.
+	#[default]
+	Implicit,
+	/// The table was defined by a `[table]` or `[[table]]` header.
+	Header,
+	/// The table was created by dotted keys, like `a` in `a.b = 1`. More dotted keys in
+	/// the same section can add to it, and headers can add sub-tables to it, but it can't
+	/// be defined again with a header.
+	Dotted,
+	/// The table was defined inline, like `a = { b = 1 }`, and can't be extended at all.
+	Inline,
+}
+
 /// Errors for the `get_<type>` methods in [`Table`].
 #[derive(Debug, PartialEq)]
 pub enum TomlGetError<'a, 'table> {
@@ -168,3 +500,12 @@ pub enum TomlGetError<'a, 'table> {
 	/// value for that key and its type.
 	TypeMismatch(&'a TomlValue<'table>, TomlValueType),
 }
+impl Display for TomlGetError<'_, '_> {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			Self::InvalidKey => f.write_str("there's no value for this key"),
+			Self::TypeMismatch(_, ty) => write!(f, "the value has the wrong type ({ty:?})"),
+		}
+	}
+}
+impl std::error::Error for TomlGetError<'_, '_> {}
@@ -1,33 +1,73 @@
 //! Defines internal boml types used for handling text.
 
-use std::{
-	borrow::Borrow,
-	fmt::{Debug, Display},
-	hash::Hash,
-	ops::{Bound, RangeBounds},
+use {
+	crate::{options::ParseOptions, Error, ErrorKind},
+	std::{
+		borrow::Borrow,
+		fmt::{Debug, Display},
+		hash::Hash,
+		ops::{Bound, RangeBounds},
+	},
 };
 
+/// The UTF-8 byte order mark. Some editors, mostly on Windows, put one at the start of
+/// files; it isn't part of the document, so it's skipped, and editors don't show it.
+pub(crate) const BOM: &str = "\u{FEFF}";
+
+/// Which bytes can be in a bare key. Bare keys are scanned for every key in a document,
+/// and a lookup table is much faster than checking each range of characters.
+static BARE_KEY_BYTES: [bool; 256] = {
+	let mut table = [false; 256];
+	let mut byte = 0;
+	while byte < table.len() {
+		table[byte] = matches!(byte as u8, b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_');
+		byte += 1;
+	}
+	table
+};
+
+/// If `byte` can be in a bare key: an ASCII letter or digit, `-`, or `_`.
+#[inline(always)]
+pub fn is_bare_key_byte(byte: u8) -> bool {
+	BARE_KEY_BYTES[byte as usize]
+}
+
 /// This is an internal boml type. It represents all of the text input to be parsed.
-#[derive(Debug)]
+#[derive(Debug, Default)]
 pub struct Text<'a> {
 	/// The text to be parsed.
 	pub text: &'a str,
 	/// The next byte that needs to be parsed.
 	pub idx: usize,
+	/// The options the text is being parsed with.
+	pub options: ParseOptions,
+	/// How deeply nested the value being parsed is. See [`Text::nest()`].
+	pub depth: usize,
 }
 impl<'a: 'b, 'b> Text<'a> {
 	/// Creates a [`Span`] from the range provided to this method.
 	pub fn excerpt<R: RangeBounds<usize>>(&self, range: R) -> Span<'b> {
 		let start = match range.start_bound() {
-			Bound::Excluded(start) => start - 1,
+			Bound::Excluded(start) => start + 1,
 			Bound::Included(start) => *start,
 			Bound::Unbounded => 0,
 		};
+		// Spans can't go past the end of the text
 		let end = match range.end_bound() {
+			// Empty spans at the very start of the text are stored like the empty
+			// string's span, since their end can't be before 0
+			Bound::Excluded(0) => {
+				return Span {
+					start: start.max(1),
+					end: 0,
+					source: self.text,
+				}
+			}
 			Bound::Excluded(end) => end - 1,
 			Bound::Included(end) => *end,
-			Bound::Unbounded => self.text.len() - 1,
-		};
+			Bound::Unbounded => self.end(),
+		}
+		.min(self.end());
 
 		Span {
 			start,
@@ -46,6 +86,16 @@ impl<'a: 'b, 'b> Text<'a> {
 		self.byte(self.idx)
 	}
 
+	/// Checks if the text starting at `self.idx` starts with `pattern`. This compares bytes,
+	/// so unlike slicing the text, it's fine if `pattern` ends in the middle of a character.
+	#[inline]
+	pub fn starts_with(&self, pattern: &str) -> bool {
+		self.text
+			.as_bytes()
+			.get(self.idx..)
+			.is_some_and(|bytes| bytes.starts_with(pattern.as_bytes()))
+	}
+
 	/// The number of remaining bytes in the text, not including the current byte.
 	#[inline]
 	pub fn remaining_bytes(&self) -> usize {
@@ -65,51 +115,230 @@ impl<'a: 'b, 'b> Text<'a> {
 		}
 	}
 
-	/// Increments `self.idx` until it hits a non-whitespace character.
-	pub fn skip_whitespace(&mut self) {
-		while let Some(byte) = self.current_byte() {
+	/// Saves the current position in the text, so it can be returned to later with
+	/// [`Text::rewind()`].
+	#[inline(always)]
+	pub fn checkpoint(&self) -> Checkpoint {
+		Checkpoint { idx: self.idx }
+	}
+	/// Returns to a position saved with [`Text::checkpoint()`].
+	#[inline(always)]
+	pub fn rewind(&mut self, checkpoint: Checkpoint) {
+		self.idx = checkpoint.idx;
+	}
+	/// Runs `parser`, and rewinds the text to where it was before `parser` ran if it
+	/// fails. This makes it easy to try parsing something one way, and fall back to
+	/// another way if that fails.
+	pub fn speculate<T, E>(
+		&mut self,
+		parser: impl FnOnce(&mut Self) -> Result<T, E>,
+	) -> Result<T, E> {
+		let checkpoint = self.checkpoint();
+		let result = parser(self);
+		if result.is_err() {
+			self.rewind(checkpoint);
+		}
+		result
+	}
+
+	/// Goes one level deeper into a table or array, which starts at `idx`. Returns a
+	/// [`NestingTooDeep`] error if that's deeper than [`ParseOptions::max_depth`]
+	/// allows. Parsers should set `depth` back once they're done with the nested value.
+	///
+	/// [`NestingTooDeep`]: crate::ErrorKind::NestingTooDeep
+	pub fn nest(&mut self, idx: usize) -> Result<(), Error<'b>> {
+		self.depth += 1;
+		if self.depth > self.options.max_depth {
+			return Err(Error {
+				src: self.excerpt(idx..=idx),
+				kind: ErrorKind::NestingTooDeep,
+				section: None,
+			});
+		}
+		Ok(())
+	}
+
+	/// Skips a UTF-8 byte order mark (U+FEFF) at the start of the text, if there is one.
+	pub fn skip_bom(&mut self) {
+		if self.idx == 0 && self.text.starts_with(BOM) {
+			self.idx = BOM.len();
+		}
+	}
+	/// Estimates how many items are in the array or inline table that starts at
+	/// `self.idx`, so it can be allocated at the right size up front. This counts the
+	/// commas before the next bracket, skipping over strings and comments, so each comma
+	/// that's counted separates 2 items that will actually be stored.
+	///
+	/// Stopping at opening brackets means nested values aren't counted, but it also means
+	/// each comma in the document is only counted once, by the innermost array or table
+	/// around it. Otherwise deeply nested values would all count the same commas, and
+	/// could be used to allocate far more memory than the document's size. Only the first
+	/// [`ESTIMATE_WINDOW`] bytes are scanned, so large values may still have to grow.
+	pub(crate) fn estimate_items(&self) -> usize {
+		let start = self.idx + 1;
+		let end = start.saturating_add(ESTIMATE_WINDOW).min(self.text.len());
+		let window = self.text.as_bytes().get(start..end).unwrap_or_default();
+
+		let mut commas = 0;
+		let mut empty = true;
+		let mut bytes = window.iter();
+		while let Some(&byte) = bytes.next() {
 			match byte {
-				b'\t' | b' ' => self.idx += 1,
-				_ => break,
+				b'[' | b']' | b'{' | b'}' => break,
+				b',' => commas += 1,
+				b'#' => {
+					bytes.find(|byte| **byte == b'\n');
+					continue;
+				}
+				// Multi-line strings are skipped as several strings in a row, which is
+				// close enough for an estimate
+				b'\'' => {
+					bytes.find(|byte| **byte == b'\'');
+				}
+				b'"' => {
+					while let Some(&byte) = bytes.next() {
+						match byte {
+							b'\\' => {
+								bytes.next();
+							}
+							b'"' => break,
+							_ => {}
+						}
+					}
+				}
+				_ if byte.is_ascii_whitespace() => continue,
+				_ => {}
 			}
+			empty = false;
+		}
+
+		if empty {
+			0
+		} else {
+			commas + 1
+		}
+	}
+
+	/// Increments `self.idx` until it hits a non-whitespace character.
+	pub fn skip_whitespace(&mut self) {
+		self.skip_words::<false>();
+		while let Some(b'\t' | b' ') = self.current_byte() {
+			self.idx += 1;
 		}
 	}
 	/// Increments `self.idx` until it hits a non-whitespace, non-newline character.
 	pub fn skip_whitespace_and_newlines(&mut self) {
-		while let Some(byte) = self.current_byte() {
-			match byte {
-				b'\t' | b' ' | b'\n' | b'\r' => self.idx += 1,
-				_ => break,
+		self.skip_words::<true>();
+		while let Some(b'\t' | b' ' | b'\n' | b'\r') = self.current_byte() {
+			self.idx += 1;
+		}
+	}
+	/// Skips spaces and tabs 8 bytes at a time, and newlines too if `NEWLINES` is true.
+	/// Stops at the first byte that isn't skipped, or when there are less than 8 bytes
+	/// left. Indentation is usually several spaces or tabs, and this is faster than
+	/// checking one byte at a time; `examples/scanning.rs` compares the two.
+	#[inline(always)]
+	fn skip_words<const NEWLINES: bool>(&mut self) {
+		const LOW_BITS: u64 = u64::from_le_bytes([0x7F; 8]);
+		const SPACES: u64 = u64::from_le_bytes([b' '; 8]);
+		const TABS: u64 = u64::from_le_bytes([b'\t'; 8]);
+		const LINE_FEEDS: u64 = u64::from_le_bytes([b'\n'; 8]);
+		const CARRIAGE_RETURNS: u64 = u64::from_le_bytes([b'\r'; 8]);
+
+		while let Some(word) = self
+			.text
+			.as_bytes()
+			.get(self.idx..)
+			.and_then(<[u8]>::first_chunk::<8>)
+		{
+			let word = u64::from_le_bytes(*word);
+			// Runs of spaces are by far the most common, so check for them first
+			if word == SPACES {
+				self.idx += 8;
+				continue;
 			}
+
+			// Adding 0x7F to a byte below 0x80 sets its top bit, unless the byte was 0, and
+			// can't carry into the next byte. So after XORing with each skipped byte, the
+			// top bit of each byte is set if it isn't that skipped byte.
+			let low = word & LOW_BITS;
+			let mut kept = ((low ^ SPACES) + LOW_BITS) & ((low ^ TABS) + LOW_BITS);
+			if NEWLINES {
+				kept &= ((low ^ LINE_FEEDS) + LOW_BITS) & ((low ^ CARRIAGE_RETURNS) + LOW_BITS);
+			}
+			// Bytes that aren't ASCII are never skipped
+			let kept = (kept | word) & !LOW_BITS;
+			if kept != 0 {
+				self.idx += kept.trailing_zeros() as usize / 8;
+				return;
+			}
+			self.idx += 8;
 		}
 	}
 }
 
+/// How many bytes [`Text::estimate_items()`] scans. The estimate only saves reallocations,
+/// so it isn't worth scanning huge values for.
+const ESTIMATE_WINDOW: usize = 8 * 1024;
+
+/// A saved position in a [`Text`]. See [`Text::checkpoint()`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Checkpoint {
+	idx: usize,
+}
+
+/// The span [`CowSpan::span()`] returns for [`CowSpan::Owned`] strings.
+static EMPTY_SPAN: Span<'static> = Span {
+	start: 1,
+	end: 0,
+	source: "",
+};
+
 /// This is an internal boml type - if you've somehow ended up with a `CowSpan`, you
 /// should probably use the [`CowSpan::as_str()`] method and get a normal string.
 ///
 /// This is essentially [`std::borrow::Cow`] for [`Span`]. It provides a few traits
 /// that `Cow` doesn't.
+#[derive(Clone)]
 pub enum CowSpan<'a> {
 	Raw(Span<'a>),
 	Modified(Span<'a>, String),
+	/// A string that wasn't parsed from a source, like one converted from the `toml`
+	/// crate's values, so it doesn't have a span.
+	Owned(String),
 }
-impl CowSpan<'_> {
+impl<'a> CowSpan<'a> {
 	/// Converts the `CowSpan` to a [`str`].
 	#[inline(always)]
 	pub fn as_str(&self) -> &str {
 		match self {
-			Self::Raw(ref raw) => &raw.source[raw.start..=raw.end],
+			Self::Raw(ref raw) => raw.as_str(),
 			Self::Modified(_, ref modified) => modified,
+			Self::Owned(ref owned) => owned,
 		}
 	}
 
 	/// Gets the span of the original, unmodified text that made this `CowSpan`.
+	/// [`CowSpan::Owned`] strings don't have one, so the span this returns for them is
+	/// meaningless: it's empty, doesn't point into any source, and has a `start` after
+	/// its `end`.
+	#[deprecated(
+		since = "0.4.0",
+		note = "returns a meaningless span for `CowSpan::Owned`; use `source_span()` instead"
+	)]
+	#[inline(always)]
+	pub fn span(&self) -> &Span<'a> {
+		self.source_span().unwrap_or(&EMPTY_SPAN)
+	}
+
+	/// Gets the span of the original, unmodified text that made this `CowSpan`, or
+	/// `None` for [`CowSpan::Owned`] strings, which weren't parsed from a source.
 	#[inline(always)]
-	pub fn span(&self) -> &Span<'_> {
+	pub fn source_span(&self) -> Option<&Span<'a>> {
 		match self {
-			Self::Raw(ref span) => span,
-			Self::Modified(ref span, _) => span,
+			Self::Raw(ref span) => Some(span),
+			Self::Modified(ref span, _) => Some(span),
+			Self::Owned(_) => None,
 		}
 	}
 }
@@ -155,6 +384,7 @@ impl Debug for CowSpan<'_> {
 					string
 				)
 			}
+			Self::Owned(string) => write!(f, "Owned string: `{string}`"),
 		}
 	}
 }
@@ -165,6 +395,7 @@ impl Display for CowSpan<'_> {
 }
 
 /// This is an internal boml type. It represents a specific section of text from [`Text`].
+#[derive(Clone, Copy)]
 pub struct Span<'a> {
 	/// Inclusive start of this span of text.
 	pub start: usize,
@@ -177,7 +408,7 @@ impl<'a: 'borrow, 'borrow> Span<'a> {
 	/// Finds the location of a character in this span, and returns its location,
 	/// relative to the entire text this span comes from.
 	pub fn find(&self, val: u8) -> Option<usize> {
-		for (idx, char_) in self.as_str().bytes().enumerate() {
+		for (idx, char_) in self.as_bytes().iter().copied().enumerate() {
 			if char_ == val {
 				return Some(idx + self.start);
 			}
@@ -195,7 +426,7 @@ impl<'a: 'borrow, 'borrow> Span<'a> {
 		let mut newline_idx = self.find(b'\n').unwrap_or(end);
 
 		// CRLF compat
-		if self.source.as_bytes()[newline_idx - 1] == b'\r' {
+		if newline_idx > 0 && self.source.as_bytes().get(newline_idx - 1) == Some(&b'\r') {
 			newline_idx -= 1;
 		}
 
@@ -221,22 +452,132 @@ impl<'a: 'borrow, 'borrow> Span<'a> {
 	/// The number of bytes in this span of text.
 	#[inline]
 	pub fn len(&self) -> usize {
-		(self.end - self.start) + 1
+		(self.end + 1).saturating_sub(self.start)
 	}
+	/// If this span doesn't cover any text. Empty spans are stored with their `end`
+	/// one byte before their `start`, like the contents of the empty string `''`.
 	#[inline]
 	pub fn is_empty(&self) -> bool {
-		self.len() == 0
+		self.end < self.start
+	}
+
+	/// The line and column this span starts at, for displaying to humans. Both are 1-based,
+	/// and columns are counted in characters rather than bytes, so they line up with what a
+	/// text editor would show.
+	pub fn line_col(&self) -> (usize, usize) {
+		let start = self.clamped_start();
+		let before = &self.source[..start];
+		let line = before.bytes().filter(|byte| *byte == b'\n').count() + 1;
+		let col = self.source[self.line_start(start)..start].chars().count() + 1;
+
+		(line, col)
+	}
+
+	/// The full text of the line this span starts on, without its line ending.
+	pub fn line_text(&self) -> &'a str {
+		let start = self.clamped_start();
+		let line_start = self.line_start(start);
+		let line_end = self.source[start..]
+			.find('\n')
+			.map(|idx| idx + start)
+			.unwrap_or(self.source.len());
+		let line = &self.source[line_start..line_end];
+
+		line.strip_suffix('\r').unwrap_or(line)
+	}
+
+	/// The start of the line containing `idx`. A byte order mark at the start of the
+	/// source isn't counted as part of the first line.
+	fn line_start(&self, idx: usize) -> usize {
+		match self.source[..idx].rfind('\n') {
+			Some(newline) => newline + 1,
+			None if idx >= BOM.len() && self.source.starts_with(BOM) => BOM.len(),
+			None => 0,
+		}
+	}
+
+	/// The span's start, moved back to the nearest character boundary within the source.
+	/// Spans for errors at the end of a document may start past the last byte.
+	pub(crate) fn clamped_start(&self) -> usize {
+		let mut start = self.start.min(self.source.len());
+		while !self.source.is_char_boundary(start) {
+			start -= 1;
+		}
+		start
+	}
+
+	/// Like [`Span::as_str()`], but never panics. The span is shrunk to fit in the source
+	/// and widened to the nearest character boundaries, so spans from errors can always be
+	/// shown, even if they end past the source or in the middle of a character.
+	pub(crate) fn clamped_str(&self) -> &'a str {
+		let start = self.clamped_start();
+		let mut end = self.end.saturating_add(1).min(self.source.len());
+		while !self.source.is_char_boundary(end) {
+			end += 1;
+		}
+
+		if self.is_empty() || end <= start {
+			""
+		} else {
+			&self.source[start..end]
+		}
 	}
 
 	/// A string covering just the bytes within this span.
+	///
+	/// Spans of parsed keys and values always cover whole characters, but spans in errors
+	/// may not: an error can point at just the first byte of a character that isn't
+	/// allowed. Instead of panicking, those spans are shrunk to fit in the source and
+	/// widened to the nearest character boundaries. Use [`Span::try_as_str()`] to tell the
+	/// two apart.
 	#[inline]
 	pub fn as_str(&self) -> &str {
-		&self.source[self.start..=self.end]
+		self.try_as_str().unwrap_or_else(|| self.clamped_str())
 	}
 	/// Identical to [`Span::as_str`], but it consumes `self`.
 	#[inline]
 	pub fn to_str(self) -> &'borrow str {
-		&self.source[self.start..=self.end]
+		self.try_as_str().unwrap_or_else(|| self.clamped_str())
+	}
+	/// A string covering exactly the bytes within this span, or `None` if the span goes
+	/// past the end of the source or starts or ends in the middle of a character.
+	#[inline]
+	pub fn try_as_str(&self) -> Option<&'a str> {
+		if self.is_empty() {
+			Some("")
+		} else {
+			self.source.get(self.start..=self.end)
+		}
+	}
+	/// The bytes within this span, shrunk to fit in the source. Unlike [`Span::as_str()`],
+	/// this doesn't care about character boundaries, so it's the way to peek at bytes that
+	/// may be in the middle of a character.
+	#[inline]
+	pub fn as_bytes(&self) -> &'a [u8] {
+		let bytes = self.source.as_bytes();
+		let end = self.end.saturating_add(1).min(bytes.len());
+		if self.is_empty() || self.start >= end {
+			&[]
+		} else {
+			&bytes[self.start..end]
+		}
+	}
+}
+impl<'a> From<&'a str> for Span<'a> {
+	/// Creates a span covering all of `source`.
+	fn from(source: &'a str) -> Self {
+		match source.len() {
+			0 => Self {
+				start: 1,
+				end: 0,
+				source,
+			},
+			len => Self {
+				start: 0,
+				end: len - 1,
+				source,
+			},
+		}
 	}
 }
 impl Debug for Span<'_> {
@@ -246,7 +587,7 @@ impl Debug for Span<'_> {
 			"Span from `{}` to `{}`: `{}`",
 			self.start,
 			self.end,
-			self.as_str()
+			self.clamped_str()
 		)
 	}
 }
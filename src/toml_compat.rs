@@ -0,0 +1,108 @@
+//! Conversions between boml's values and the [`toml`] crate's, for code that's moving
+//! from one to the other. Requires the `toml-compat` feature.
+//!
+//! ```
+//! use boml::prelude::*;
+//!
+//! let toml = Toml::parse("[package]\nname = 'boml'\n").unwrap();
+//! let value: toml::Table = (&*toml).try_into().unwrap();
+//! assert_eq!(value["package"]["name"].as_str(), Some("boml"));
+//!
+//! let table = TomlTable::try_from(&value).unwrap();
+//! assert_eq!(&table, &*toml);
+//! ```
+//!
+//! [`toml::Value`] and [`toml::Table`] have their own `try_from()` methods, for
+//! serializing values, so use `try_into()` to convert boml's values to them.
+//!
+//! Converting doesn't lose any information, except for where values were written:
+//! keys converted from the [`toml`] crate don't have a source, so [`Table::key_span()`]
+//! returns `None` for them. Date and time values can't be converted either way, since
+//! boml doesn't support them yet.
+
+use {
+	crate::crate_prelude::*,
+	std::fmt::{self, Display},
+};
+
+/// A date or time value was converted. boml doesn't support these yet; see
+/// [`ErrorKind::UnsupportedDateTime`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct UnsupportedDateTime;
+impl Display for UnsupportedDateTime {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.write_str("date and time values aren't supported yet")
+	}
+}
+impl std::error::Error for UnsupportedDateTime {}
+
+impl TryFrom<&TomlValue<'_>> for toml::Value {
+	type Error = UnsupportedDateTime;
+
+	fn try_from(value: &TomlValue<'_>) -> Result<Self, Self::Error> {
+		Ok(match value {
+			TomlValue::String(string) => Self::String(string.as_str().to_string()),
+			TomlValue::Integer(int) => Self::Integer(*int),
+			TomlValue::Float(float) => Self::Float(*float),
+			TomlValue::Boolean(bool) => Self::Boolean(*bool),
+			TomlValue::Array(array) => Self::Array(
+				array
+					.iter()
+					.map(TryInto::try_into)
+					.collect::<Result<_, _>>()?,
+			),
+			TomlValue::Table(table) => Self::Table(table.try_into()?),
+			TomlValue::OffsetDateTime
+			| TomlValue::LocalDateTime
+			| TomlValue::LocalDate
+			| TomlValue::LocalTime => return Err(UnsupportedDateTime),
+		})
+	}
+}
+impl TryFrom<&Table<'_>> for toml::Table {
+	type Error = UnsupportedDateTime;
+
+	fn try_from(table: &Table<'_>) -> Result<Self, Self::Error> {
+		table
+			.iter()
+			.map(|(key, value)| Ok((key.to_string(), value.try_into()?)))
+			.collect()
+	}
+}
+
+impl TryFrom<&toml::Value> for TomlValue<'static> {
+	type Error = UnsupportedDateTime;
+
+	fn try_from(value: &toml::Value) -> Result<Self, Self::Error> {
+		Ok(match value {
+			toml::Value::String(string) => Self::String(owned_string(string)),
+			toml::Value::Integer(int) => Self::Integer(*int),
+			toml::Value::Float(float) => Self::Float(*float),
+			toml::Value::Boolean(bool) => Self::Boolean(*bool),
+			toml::Value::Datetime(_) => return Err(UnsupportedDateTime),
+			toml::Value::Array(array) => {
+				Self::Array(array.iter().map(Self::try_from).collect::<Result<_, _>>()?)
+			}
+			toml::Value::Table(table) => Self::Table(Table::try_from(table)?),
+		})
+	}
+}
+impl TryFrom<&toml::Table> for Table<'static> {
+	type Error = UnsupportedDateTime;
+
+	fn try_from(table: &toml::Table) -> Result<Self, Self::Error> {
+		let mut converted = Table::with_hasher(table.len(), Default::default());
+		for (key, value) in table {
+			converted
+				.map
+				.insert(owned_string(key), TomlValue::try_from(value)?);
+		}
+
+		Ok(converted)
+	}
+}
+
+/// A [`CowSpan`] for a string that wasn't parsed from a source.
+fn owned_string(string: &str) -> CowSpan<'static> {
+	CowSpan::Owned(string.to_string())
+}
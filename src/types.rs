@@ -6,7 +6,7 @@ use crate::crate_prelude::*;
 use crate::text::*;
 
 /// A value in TOML.
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum TomlValue<'a> {
 	/// A basic or literal string. If it's a basic string with escapes,
 	/// those escapes have already been processed.
@@ -76,7 +76,7 @@ impl<'a> TomlValue<'a> {
 		}
 	}
 	/// Returns the array within this value, if it's an array; otherwise, fails.
-	pub fn array(&self) -> Option<&Vec<Self>> {
+	pub fn array(&self) -> Option<&[Self]> {
 		match self {
 			Self::Array(array) => Some(array),
 			_ => None,
@@ -91,6 +91,37 @@ impl<'a> TomlValue<'a> {
 	}
 }
 
+impl<'a> From<&'a str> for TomlValue<'a> {
+	fn from(value: &'a str) -> Self {
+		Self::String(CowSpan::Raw(Span::from(value)))
+	}
+}
+impl From<i64> for TomlValue<'_> {
+	fn from(value: i64) -> Self {
+		Self::Integer(value)
+	}
+}
+impl From<f64> for TomlValue<'_> {
+	fn from(value: f64) -> Self {
+		Self::Float(value)
+	}
+}
+impl From<bool> for TomlValue<'_> {
+	fn from(value: bool) -> Self {
+		Self::Boolean(value)
+	}
+}
+impl<'a> From<Vec<TomlValue<'a>>> for TomlValue<'a> {
+	fn from(value: Vec<TomlValue<'a>>) -> Self {
+		Self::Array(value)
+	}
+}
+impl<'a> From<Table<'a>> for TomlValue<'a> {
+	fn from(value: Table<'a>) -> Self {
+		Self::Table(value)
+	}
+}
+
 /// The basic value types in TOML.
 #[derive(Debug, PartialEq, Eq, Clone, Copy)]
 pub enum TomlValueType {
@@ -114,3 +145,13 @@ pub struct Key<'a> {
 	/// that comes after the dot.
 	pub child: Option<Box<Key<'a>>>,
 }
+impl<'a> Key<'a> {
+	/// Where this part of the key is in the source. Keys are only made by the parser, so
+	/// their text is never [`CowSpan::Owned`] and always has a span.
+	pub(crate) fn span(&self) -> Span<'a> {
+		match self.text.source_span() {
+			Some(span) => *span,
+			None => unreachable!("keys are always parsed from a source"),
+		}
+	}
+}
@@ -0,0 +1,75 @@
+use {
+	boml::prelude::*,
+	std::{
+		alloc::{GlobalAlloc, Layout, System},
+		fmt::Write,
+		sync::atomic::{AtomicUsize, Ordering},
+	},
+};
+
+/// Tracks how many bytes are allocated, and the most that were allocated at once.
+struct TrackingAllocator;
+
+static CURRENT: AtomicUsize = AtomicUsize::new(0);
+static PEAK: AtomicUsize = AtomicUsize::new(0);
+
+fn track(added: usize, removed: usize) {
+	let current = CURRENT.fetch_add(added, Ordering::Relaxed) + added;
+	PEAK.fetch_max(current, Ordering::Relaxed);
+	CURRENT.fetch_sub(removed, Ordering::Relaxed);
+}
+
+unsafe impl GlobalAlloc for TrackingAllocator {
+	unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+		track(layout.size(), 0);
+		System.alloc(layout)
+	}
+
+	unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+		track(0, layout.size());
+		System.dealloc(ptr, layout)
+	}
+
+	unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+		track(new_size, layout.size());
+		System.realloc(ptr, layout, new_size)
+	}
+}
+
+#[global_allocator]
+static ALLOCATOR: TrackingAllocator = TrackingAllocator;
+
+/// The most memory that was allocated at once while parsing `source`, on top of what was
+/// already allocated.
+fn peak_allocation(source: &str) -> usize {
+	let start = CURRENT.load(Ordering::Relaxed);
+	PEAK.store(start, Ordering::Relaxed);
+	let toml = Toml::parse(source).unwrap();
+	let peak = PEAK.load(Ordering::Relaxed);
+	drop(toml);
+	peak - start
+}
+
+/// Test that commas in strings can't make small arrays and inline tables reserve far more
+/// memory than the document's size.
+#[test]
+fn comma_heavy_strings() {
+	let commas = ",".repeat(8000);
+	let mut arrays = String::new();
+	let mut tables = String::new();
+	for idx in 0..128 {
+		writeln!(arrays, "a{idx} = ['{commas}']").unwrap();
+		writeln!(tables, "a{idx} = {{ b = '{commas}' }}").unwrap();
+	}
+
+	// The tests run on several threads, so other tests' allocations can be counted too;
+	// the limit leaves room for that, but not for a few hundred slots per value
+	for source in [arrays, tables] {
+		let peak = peak_allocation(&source);
+		assert!(
+			peak < source.len() / 2,
+			"parsing {} bytes allocated {peak} bytes",
+			source.len()
+		);
+	}
+}
@@ -0,0 +1,117 @@
+use boml::{comments::CommentPlacement, prelude::*};
+
+const SOURCE: &str = "# Leading comment for the root key
+# It has 2 lines
+root = 1 # Trailing comment for root
+
+[table] # Trailing comment for table
+# Leading comment for 'quoted'
+'quoted' = [
+	1, # Comments in arrays aren't captured
+	2,
+] # Trailing comment for 'quoted'
+
+# Leading comment for the array of tables
+[[array]]
+dotted.key = true\t#\tTrailing comment for dotted.key\r
+# Comment at the end of the file";
+
+fn capture() -> ParseOptions {
+	ParseOptions::new().capture_comments(true)
+}
+
+/// Test that comments are attached to the right items, with the right placement.
+#[test]
+fn placement() {
+	use CommentPlacement::*;
+
+	let toml = Toml::parse_with(SOURCE, &capture()).unwrap();
+	let comments: Vec<_> = toml
+		.comments()
+		.iter()
+		.map(|comment| {
+			(
+				comment.text(),
+				comment.placement,
+				comment.item.as_ref().map(|item| item.as_str()),
+			)
+		})
+		.collect();
+
+	assert_eq!(
+		comments,
+		[
+			("Leading comment for the root key", Leading, Some("root")),
+			("It has 2 lines", Leading, Some("root")),
+			("Trailing comment for root", Trailing, Some("root")),
+			("Trailing comment for table", Trailing, Some("[table]")),
+			("Leading comment for 'quoted'", Leading, Some("'quoted'")),
+			("Trailing comment for 'quoted'", Trailing, Some("'quoted'")),
+			(
+				"Leading comment for the array of tables",
+				Leading,
+				Some("[[array]]")
+			),
+			(
+				"Trailing comment for dotted.key",
+				Trailing,
+				Some("dotted.key")
+			),
+			("Comment at the end of the file", Leading, None),
+		]
+	);
+
+	// Spans cover the whole comment, but not the line ending
+	assert_eq!(
+		toml.comments()[7].span.as_str(),
+		"#\tTrailing comment for dotted.key"
+	);
+}
+
+/// Test that the comments for a key can be found from its span.
+#[test]
+fn comments_for() {
+	let toml = Toml::parse_with(SOURCE, &capture()).unwrap();
+	let table = toml.get_table("table").unwrap();
+
+	let span = table.key_span("quoted").unwrap();
+	let comments: Vec<_> = toml.comments_for(span).map(|c| c.text()).collect();
+	assert_eq!(
+		comments,
+		[
+			"Leading comment for 'quoted'",
+			"Trailing comment for 'quoted'"
+		]
+	);
+
+	let span = toml.key_span("root").unwrap();
+	assert_eq!(toml.comments_for(span).count(), 3);
+}
+
+/// Test that comments aren't kept unless they're asked for.
+#[test]
+fn disabled_by_default() {
+	assert!(Toml::parse(SOURCE).unwrap().comments().is_empty());
+	assert!(Toml::parse_with(SOURCE, &ParseOptions::new())
+		.unwrap()
+		.comments()
+		.is_empty());
+	assert!(!ParseOptions::default().capture_comments);
+}
+
+/// Test comments that don't have an item to attach to.
+#[test]
+fn without_items() {
+	for source in ["#", "# Only a comment\n", "\u{feff}  # After a BOM\r\n\n"] {
+		let toml = Toml::parse_with(source, &capture()).unwrap();
+		let [comment] = toml.comments() else {
+			panic!("Expected 1 comment in {source:?}");
+		};
+		assert_eq!(comment.placement, CommentPlacement::Leading);
+		assert!(comment.item.is_none());
+		assert_eq!(
+			comment.span.as_str(),
+			source.trim_matches(['\u{feff}', ' ', '\r', '\n'])
+		);
+	}
+}
@@ -1,4 +1,4 @@
-/// Tests boml by parsing `Cargo.toml` files from well-known crates.
+//! Tests boml by parsing `Cargo.toml` files from well-known crates.
 
 const SYN_URL: &str = "https://raw.githubusercontent.com/dtolnay/syn/98a90d70105f9b43f08eba091d6df1ec490a56e9/Cargo.toml";
 
@@ -58,9 +58,10 @@ fn parse(source: &str) -> Toml<'_> {
 	match Toml::parse(source) {
 		Ok(toml) => toml,
 		Err(error) => {
-			let Error { start, end, kind } = error;
+			let Error { src, kind, .. } = error;
+			let (start, end) = (src.start, src.end);
 
-			let more_ctx_start = if start > 15 { start - 15 } else { 0 };
+			let more_ctx_start = start.saturating_sub(15);
 			let more_ctx_end = if source.len() - 16 > end {
 				end + 15
 			} else {
@@ -0,0 +1,51 @@
+#![cfg(feature = "miette")]
+
+use {boml::prelude::*, miette::Diagnostic};
+
+/// Test that errors are converted to diagnostics with a code, help, and label.
+#[test]
+fn diagnostic() {
+	let source = "a = 1\nkey = \"unclosed\n";
+	let diagnostic = Toml::parse(source).unwrap_err().into_diagnostic();
+	assert_eq!(diagnostic.to_string(), "unclosed string");
+	assert_eq!(
+		diagnostic.code().unwrap().to_string(),
+		"E0007-unclosed-string"
+	);
+	assert!(diagnostic.help().is_some());
+
+	let labels: Vec<_> = diagnostic.labels().unwrap().collect();
+	assert_eq!(labels.len(), 1);
+	assert_eq!(labels[0].offset(), 12);
+	assert_eq!(labels[0].len(), 9);
+
+	let contents = diagnostic
+		.source_code()
+		.unwrap()
+		.read_span(labels[0].inner(), 0, 0)
+		.unwrap();
+	assert_eq!(contents.line(), 1);
+	assert!(contents.name().is_none());
+}
+
+/// Test that file names are attached to the diagnostic's source.
+#[test]
+fn named() {
+	let err = Toml::parse("a = nope\n").unwrap_err();
+	let diagnostic = err.with_file_name("config.toml").into_diagnostic();
+	let label = diagnostic.labels().unwrap().next().unwrap();
+
+	let contents = diagnostic
+		.source_code()
+		.unwrap()
+		.read_span(label.inner(), 0, 0)
+		.unwrap();
+	assert_eq!(contents.name(), Some("config.toml"));
+
+	let report = miette::Report::from(Toml::parse("a = nope\n").unwrap_err());
+	assert_eq!(report.to_string(), "unrecognised value");
+	assert_eq!(
+		report.help().unwrap().to_string(),
+		"string values must be quoted, like `\"nope\"`"
+	);
+}
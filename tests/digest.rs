@@ -0,0 +1,73 @@
+use boml::{prelude::*, validate_digest};
+
+/// Test that documents with the same content have the same digest, no matter how they're
+/// formatted.
+#[test]
+fn formatting_independent() {
+	let first = validate_digest(concat!(
+		"# Server config\n",
+		"name = 'server'\n",
+		"port = 8080\n",
+		"\n",
+		"[limits]\n",
+		"rate = 1.5\n",
+		"burst = 0x10\n",
+		"\n",
+		"[[users]]\n",
+		"name = \"admin\"\n",
+		"[[users]]\n",
+		"name = 'guest'\n",
+	))
+	.unwrap();
+	let second = validate_digest(concat!(
+		"users = [ { name = 'admin' }, { name = 'guest' } ]\r\n",
+		"limits.burst = 16     # in requests\r\n",
+		"limits.rate = 15e-1\r\n",
+		"port = +8_080\r\n",
+		"\"name\" = \"server\"",
+	))
+	.unwrap();
+	assert_eq!(first, second);
+	assert_eq!(
+		first,
+		ValidationReport {
+			sections: 3,
+			keys: 8,
+			max_depth: 3,
+			digest: first.digest,
+		}
+	);
+}
+
+/// Test that changing the document's content changes its digest.
+#[test]
+fn content_dependent() {
+	let original = validate_digest("a = 1\n[table]\nb = 'x'\nc = [1, 2]\n").unwrap();
+	let changes = [
+		// Changing a value
+		"a = 2\n[table]\nb = 'x'\nc = [1, 2]\n",
+		"a = 1\n[table]\nb = 'y'\nc = [1, 2]\n",
+		"a = 1\n[table]\nb = 'x'\nc = [2, 1]\n",
+		// Changing a value's type
+		"a = 1.0\n[table]\nb = 'x'\nc = [1, 2]\n",
+		"a = '1'\n[table]\nb = 'x'\nc = [1, 2]\n",
+		// Renaming or moving a key
+		"A = 1\n[table]\nb = 'x'\nc = [1, 2]\n",
+		"[table]\na = 1\nb = 'x'\nc = [1, 2]\n",
+		// Moving values between arrays
+		"a = 1\n[table]\nb = 'x'\nc = [[1], 2]\n",
+		"a = 1\n[table]\nb = 'x'\nc = [1, 2, []]\n",
+	];
+
+	for changed in changes {
+		let report = validate_digest(changed).unwrap();
+		assert_ne!(report.digest, original.digest, "{changed}");
+	}
+}
+
+/// Test that invalid documents are reported as errors.
+#[test]
+fn invalid() {
+	let err = validate_digest("a = 1\nb = nope\n").unwrap_err();
+	assert_eq!(err.kind, TomlErrorKind::UnrecognisedValue);
+}
@@ -0,0 +1,142 @@
+use boml::prelude::*;
+
+const CARGO_TOML: &str = concat!(
+	"# My cool crate\n",
+	"[package]\n",
+	"name = \"boml\" # The name\n",
+	"version    =   \"0.3.1\"\n",
+	"\n",
+	"# Dependencies go here\n",
+	"[dependencies]\n",
+	"json = { version = \"0.12\" }\n",
+	"\n",
+	"[[bin]]\n",
+	"name = 'first'\n",
+);
+
+/// Test that editing one value leaves the rest of the document untouched.
+#[test]
+fn set_existing() {
+	let mut doc = TomlDocument::parse(CARGO_TOML).unwrap();
+	doc.set("package.version", &"0.4.0".into()).unwrap();
+	assert_eq!(
+		doc.as_str(),
+		CARGO_TOML.replace("\"0.3.1\"", "'0.4.0'").as_str()
+	);
+
+	doc.set("package.name", &TomlValue::Integer(5)).unwrap();
+	assert_eq!(
+		doc.as_str(),
+		CARGO_TOML
+			.replace("\"0.3.1\"", "'0.4.0'")
+			.replace("\"boml\"", "5")
+			.as_str()
+	);
+
+	let toml = doc.toml();
+	let package = toml.get_table("package").unwrap();
+	assert_eq!(package.get_integer("name"), Ok(5));
+	assert_eq!(package.get_string("version"), Ok("0.4.0"));
+}
+
+/// Test that new keys are added to the end of the section they belong in, and that new
+/// keys in the root table go after the comments at the top of the document.
+#[test]
+fn set_new() {
+	let mut doc = TomlDocument::parse(CARGO_TOML).unwrap();
+	doc.set("package.edition", &"2021".into()).unwrap();
+	doc.set("dependencies.reqwest.version", &"0.11".into())
+		.unwrap();
+	doc.set("top", &true.into()).unwrap();
+	doc.set("dotted.key", &vec![1.into(), 2.5.into()].into())
+		.unwrap();
+
+	let expected = CARGO_TOML
+		.replace("\"0.3.1\"\n", "\"0.3.1\"\nedition = '2021'\n")
+		.replace(
+			"{ version = \"0.12\" }\n",
+			"{ version = \"0.12\" }\nreqwest.version = '0.11'\n",
+		)
+		.replace(
+			"# My cool crate\n",
+			"# My cool crate\ntop = true\ndotted.key = [1, 2.5]\n",
+		);
+	assert_eq!(doc.as_str(), expected);
+
+	let toml = doc.toml();
+	assert!(toml.get_boolean("top").unwrap());
+	let reqwest = toml
+		.get_table("dependencies")
+		.unwrap()
+		.get_table("reqwest")
+		.unwrap();
+	assert_eq!(reqwest.get_string("version"), Ok("0.11"));
+}
+
+/// Test that removing a key removes its entire line.
+#[test]
+fn remove() {
+	let mut doc = TomlDocument::parse(CARGO_TOML).unwrap();
+	doc.remove("package.name").unwrap();
+	assert_eq!(
+		doc.as_str(),
+		CARGO_TOML.replace("name = \"boml\" # The name\n", "")
+	);
+	assert_eq!(doc.remove("package.name"), Err(EditError::InvalidKey));
+}
+
+/// Test that edits which can't be made fail without changing the document.
+#[test]
+fn invalid_edits() {
+	let mut doc = TomlDocument::parse(CARGO_TOML).unwrap();
+	assert_eq!(doc.set("", &1.into()), Err(EditError::InvalidPath));
+	assert_eq!(doc.set("a b", &1.into()), Err(EditError::InvalidPath));
+	assert_eq!(
+		doc.set("package.name.first", &1.into()),
+		Err(EditError::InvalidEdit)
+	);
+	assert_eq!(
+		doc.set("time", &TomlValue::LocalDate),
+		Err(EditError::UnsupportedValue)
+	);
+	// Keys in arrays of tables are ambiguous
+	assert_eq!(doc.remove("bin.name"), Err(EditError::InvalidKey));
+	assert_eq!(doc.as_str(), CARGO_TOML);
+}
+
+/// Test that documents starting with a byte order mark keep it, and that keys added to
+/// the root table go after it.
+#[test]
+fn byte_order_mark() {
+	let mut doc = TomlDocument::parse("\u{FEFF}[table]\nx = 1\n").unwrap();
+	doc.set("top", &true.into()).unwrap();
+	doc.set("table.x", &2.into()).unwrap();
+	assert_eq!(doc.as_str(), "\u{FEFF}top = true\n[table]\nx = 2\n");
+
+	let mut doc = TomlDocument::parse("\u{FEFF}").unwrap();
+	doc.set("a", &1.into()).unwrap();
+	assert_eq!(doc.as_str(), "\u{FEFF}a = 1\n");
+}
+
+/// Test that new keys in an empty root table go after the comments at the top of the
+/// document, and that new lines use the document's line endings.
+#[test]
+fn leading_comments_and_line_endings() {
+	let mut doc = TomlDocument::parse("# Header\n\n# More\n\n[table]\n").unwrap();
+	doc.set("top", &true.into()).unwrap();
+	assert_eq!(doc.as_str(), "# Header\n\n# More\ntop = true\n\n[table]\n");
+
+	let mut doc = TomlDocument::parse("# Only a comment").unwrap();
+	doc.set("a", &1.into()).unwrap();
+	assert_eq!(doc.as_str(), "# Only a comment\na = 1\n");
+
+	let mut doc = TomlDocument::parse("# Header\r\n[table]\r\nx = 1\r\n").unwrap();
+	doc.set("top", &true.into()).unwrap();
+	doc.set("table.y", &2.into()).unwrap();
+	doc.set("other.z", &3.into()).unwrap();
+	assert_eq!(
+		doc.as_str(),
+		"# Header\r\ntop = true\r\nother.z = 3\r\n[table]\r\nx = 1\r\ny = 2\r\n"
+	);
+	assert!(!doc.as_str().replace("\r\n", "").contains('\n'));
+}
@@ -0,0 +1,128 @@
+use boml::prelude::*;
+
+/// Documents that should parse the same way no matter how they end. Each one ends with a
+/// newline; the tests below change how they end.
+const CORPUS: &[&str] = &[
+	// From the parsing tests
+	"val1 = true\nval2 = false\n5678 = true\ndash-ed = true\nunder_score = true\n",
+	"'val0.1.1' = true\n'ʎǝʞ' = true\n\"quoted 'key'\" = true\n'quoted \"key\" 2' = true\n",
+	"table.bool = true\ntable.string = 'hi'\ntable. spaced = 69\ntable  .infinity = -inf\n",
+	"single = 'a demo sentence'\nmulti = '''Bruhhhh I gotta write\n*another*\n:('''\n",
+	concat!(
+		"normal = \"normality 100\"\n",
+		"quotes = \"Bro I got \\\"quotes\\\"\" \n",
+		"escapes = \"\\t\\n\\r\\\\\" \n",
+		"multi = \"\"\"me when\\ni do multiline\\r pretty neat\"\"\"\n",
+		"whitespace = \"\"\"white\\    \n\n\n\r\n    space\"\"\"\n",
+	),
+	"hex = 0x10\ndecimal = 10\noctal = 0o10\nbinary = 0b10\nlmao = -0\n",
+	"fractional = 0.345\nexponential = 4e2\ncombined = 7.27e2\ninfinity = -inf\n",
+	"empty = {}\ninline = { name = 'inline', num = inf }\n\n[table1]\nname = 'table1'\n",
+	"strings = ['hi', 'hello']\nnested = ['me', ['when i', 'nest']]\nsingle = [2]\n",
+	"tables = [{name = 'bruh'}, {name = 'bruh 2 electric boogaloo'}]\n",
+	"[[entry]]\nidx = 0\n\n[[entry]]\nidx = 1\nvalue = true\n",
+	"   val1 = true\r\nval2=      false\n\r\n\r\n\nval3  =true\n[parent .  \"child.dotted\"]\nyippee = true\n",
+	// Each kind of value, as short as possible. NaN is left out, since it never compares
+	// equal to itself
+	"c = 1\n",
+	"c=1\n",
+	"c = 0\n",
+	"c = -1\n",
+	"c = 1.5\n",
+	"c = inf\n",
+	"c = ''\n",
+	"c = \"\"\n",
+	"c = ''''''\n",
+	"c = \"\"\"\"\"\"\n",
+	"c = 'x'\n",
+	"c = []\n",
+	"c = [1]\n",
+	"c = [1,2,]\n",
+	"c = [1,2, ]\n",
+	"c = [\n1,\n]\n",
+	"c = [[1,],]\n",
+	"c = [[1, 2], [3]]\n",
+	"c = [[], [[]]]\n",
+	"c = {}\n",
+	"c = { d = 1 }\n",
+	"c = true\n",
+	"c.d = 1\n",
+	"'c' = 1\n",
+	// Headers and comments
+	"[table]\n",
+	"[[table]]\n",
+	"[a.b]\n",
+	"a = 1\n[table]\n",
+	"# just a comment\n",
+	"a = 1\n# a comment\n",
+];
+
+/// Parses `source`, and checks that it produces the same table as `canonical`.
+fn assert_same(canonical: &str, source: &str) {
+	let expected = match Toml::parse(canonical) {
+		Ok(toml) => toml,
+		Err(err) => panic!("{canonical:?} failed to parse:\n{err}"),
+	};
+	let parsed = match Toml::parse(source) {
+		Ok(toml) => toml,
+		Err(err) => panic!("{source:?} failed to parse:\n{err}"),
+	};
+	assert_eq!(*parsed, *expected, "{source:?}");
+}
+
+/// Test that documents don't need to end with a newline.
+#[test]
+fn missing_newline() {
+	for canonical in CORPUS {
+		assert_same(canonical, &canonical[..canonical.len() - 1]);
+	}
+
+	// Or end with a CRLF
+	for canonical in CORPUS {
+		let stripped = &canonical[..canonical.len() - 1];
+		assert_same(canonical, &format!("{stripped}\r\n"));
+		assert_same(canonical, &format!("{stripped}\r\n\r\n"));
+	}
+}
+
+/// Test that whitespace at the end of a document is ignored.
+#[test]
+fn trailing_whitespace() {
+	for canonical in CORPUS {
+		let stripped = &canonical[..canonical.len() - 1];
+
+		assert_same(canonical, &format!("{canonical}  "));
+		assert_same(canonical, &format!("{canonical}\t\n  \n\n"));
+		assert_same(canonical, &format!("{stripped}  "));
+		assert_same(canonical, &format!("{stripped} \t"));
+	}
+}
+
+/// Test that documents can end with a comment, without a newline after it.
+#[test]
+fn final_comment() {
+	for canonical in CORPUS {
+		let stripped = &canonical[..canonical.len() - 1];
+
+		assert_same(canonical, &format!("{stripped} # comment"));
+		assert_same(canonical, &format!("{stripped}#"));
+		assert_same(canonical, &format!("{canonical}# comment"));
+		assert_same(canonical, &format!("{canonical}# comment\n"));
+	}
+}
+
+/// Test that documents that end without a value are still errors.
+#[test]
+fn truncated() {
+	for source in [
+		"c =",
+		"c = ",
+		"c =\n",
+		"c = # comment",
+		"c",
+		"[table",
+		"[[table]",
+	] {
+		assert!(Toml::parse(source).is_err(), "{source:?}");
+	}
+}
@@ -0,0 +1,667 @@
+use boml::{prelude::*, text::Span};
+
+/// Test that documents which end in the middle of something are detected as truncated,
+/// and report the table they were cut off in.
+#[test]
+fn truncation() {
+	let complete = concat!(
+		"[package]\n",
+		"name = \"boml\"\n",
+		"\n",
+		"[dependencies]\n",
+		"list = [1, 2, 3]\n",
+	);
+	assert!(Toml::parse(complete).is_ok());
+
+	// Cut off mid-string
+	let err = Toml::parse(&complete[..20]).unwrap_err();
+	assert_eq!(err.kind, TomlErrorKind::UnclosedString);
+	assert!(err.is_truncation());
+	assert_eq!(err.section.unwrap().as_str(), "[package]");
+
+	// Cut off mid-header, which is reported in the previous table
+	let err = Toml::parse(&complete[..30]).unwrap_err();
+	assert!(err.is_truncation());
+	assert_eq!(err.section.unwrap().as_str(), "[package]");
+
+	// Cut off mid-array, with trailing whitespace
+	let source = format!("{}  \n", &complete[..51]);
+	let err = Toml::parse(&source).unwrap_err();
+	assert_eq!(err.kind, TomlErrorKind::UnclosedBracket);
+	assert!(err.is_truncation());
+	assert_eq!(err.section.unwrap().as_str(), "[dependencies]");
+	assert!(err.to_string().contains("may be incomplete"));
+
+	// Cut off in the root table
+	let err = Toml::parse("name = ").unwrap_err();
+	assert!(err.is_truncation());
+	assert!(err.section.is_none());
+}
+
+/// Test that errors in the middle of a document aren't detected as truncation.
+#[test]
+fn not_truncation() {
+	let err = Toml::parse("[table\nkey = 1\n").unwrap_err();
+	assert_eq!(err.kind, TomlErrorKind::UnclosedBracket);
+	assert!(!err.is_truncation());
+
+	let err = Toml::parse("[table]\nkey = [1 2]\nother = 1\n").unwrap_err();
+	assert!(!err.is_truncation());
+	assert_eq!(err.section.unwrap().as_str(), "[table]");
+	assert!(!err.to_string().contains("may be incomplete"));
+}
+
+/// Test that error positions are converted to 1-based lines and character columns.
+#[test]
+fn line_col() {
+	// First line
+	let err = Toml::parse("key = nope\n").unwrap_err();
+	assert_eq!(err.line_col(), (1, 7));
+	assert_eq!(err.line_text(), "key = nope");
+
+	// Columns count characters, not bytes
+	let err = Toml::parse("[table]\n\"ключ\" = nope\n").unwrap_err();
+	assert_eq!(err.line_col(), (2, 10));
+	assert_eq!(err.line_text(), "\"ключ\" = nope");
+
+	// CRLF line endings aren't included in the line's text
+	let err = Toml::parse("a = 1\r\nb = 2\r\nc = nope\r\nd = 4\r\n").unwrap_err();
+	assert_eq!(err.line_col(), (3, 5));
+	assert_eq!(err.line_text(), "c = nope");
+
+	// End of the file
+	let err = Toml::parse("a = 1\nb = \"unclosed").unwrap_err();
+	assert_eq!(err.line_col(), (2, 5));
+	assert_eq!(err.line_text(), "b = \"unclosed");
+	let err = Toml::parse("a = 1\nb =").unwrap_err();
+	assert_eq!(err.line_col().0, 2);
+	assert_eq!(err.line_text(), "b =");
+	assert_eq!(err.kind, TomlErrorKind::NoValueInAssignment);
+}
+
+/// Test that errors are rendered with the offending line and a caret underline.
+#[test]
+fn display() {
+	let err = Toml::parse("a = 1\nkey = nope\n").unwrap_err();
+	assert_eq!(
+		err.to_string(),
+		concat!(
+			"error[E0008-unrecognised-value]: unrecognised value\n",
+			" --> line 2, column 7\n",
+			"  |\n",
+			"2 | key = nope\n",
+			"  |       ^^^^\n",
+			"  = help: string values must be quoted, like `\"nope\"`",
+		)
+	);
+
+	// Tabs are kept so the carets line up
+	let err = Toml::parse("\t'ключ' = nope\n").unwrap_err();
+	assert!(err
+		.to_string()
+		.contains("1 | \t'ключ' = nope\n  | \t         ^^^^\n"));
+
+	// Only the first line of multiline spans is underlined
+	let err = Toml::parse("key = [\n  1,\n  2\n").unwrap_err();
+	assert!(err
+		.to_string()
+		.contains("1 | key = [\n  |       ^...\n  = note: the document ends"));
+
+	// At the end of the file
+	let err = Toml::parse("a = 1\nb =").unwrap_err();
+	assert!(err.to_string().contains("2 | b =\n  | ^^^\n"));
+
+	// Empty sources
+	let err = TomlError {
+		src: Span::from(""),
+		kind: TomlErrorKind::NoValueInAssignment,
+		section: None,
+	};
+	assert!(err.to_string().contains("1 | \n  | ^"));
+}
+
+/// Test that errors can be formatted no matter where they are in the source - at its very
+/// start or end, next to multi-byte characters, or in an empty source.
+#[test]
+fn format_boundaries() {
+	fn format_all(err: &TomlError<'_>) -> (String, String, OwnedTomlError) {
+		(format!("{err:?}"), err.to_string(), err.to_owned_error())
+	}
+
+	// The very first byte
+	let err = Toml::parse("🦀🦀 = 1\n").unwrap_err();
+	let (debug, display, owned) = format_all(&err);
+	assert!(debug.contains("`🦀`"));
+	assert!(display.ends_with("1 | 🦀🦀 = 1\n  | ^"));
+	assert_eq!(owned.excerpt, "🦀");
+	assert_eq!(owned.context, "🦀🦀 = 1");
+
+	// The very last byte
+	let err = Toml::parse("a = 1\nb = [1, 2").unwrap_err();
+	let (debug, display, owned) = format_all(&err);
+	assert!(debug.contains("`[1, 2`"));
+	assert!(display.contains("2 | b = [1, 2\n  |     ^^^^^"));
+	assert_eq!(owned.context, "a = 1\nb = [1, 2");
+
+	// Inside a line of emoji
+	let err = Toml::parse("key = 🦀 🦀\n").unwrap_err();
+	let (debug, display, owned) = format_all(&err);
+	assert!(debug.contains("`🦀`"));
+	assert!(display.ends_with("1 | key = 🦀 🦀\n  |       ^"));
+	assert_eq!(owned.excerpt, "🦀");
+	let err = Toml::parse("a = 0x🦀\n").unwrap_err();
+	assert!(format_all(&err).1.ends_with("1 | a = 0x🦀\n  |     ^^^"));
+
+	// Spans that end in the middle of a character, or past the end of the source
+	for (start, end) in [(0, 0), (4, 4), (5, 5), (2, 100), (100, 200)] {
+		let err = TomlError {
+			src: Span {
+				start,
+				end,
+				source: "a = 🦀",
+			},
+			kind: TomlErrorKind::UnrecognisedValue,
+			section: None,
+		};
+		let (_, display, owned) = format_all(&err);
+		assert!(display.contains("1 | a = 🦀\n"));
+		assert_eq!(owned.context, "a = 🦀");
+	}
+
+	// An empty source
+	let past_end = Span {
+		start: 0,
+		end: 0,
+		source: "",
+	};
+	for src in [Span::from(""), past_end] {
+		let err = TomlError {
+			src,
+			kind: TomlErrorKind::NoValueInAssignment,
+			section: None,
+		};
+		let (debug, display, owned) = format_all(&err);
+		assert!(debug.contains("``"));
+		assert!(display.contains("1 | \n  | ^"));
+		assert_eq!(owned.line_col, (1, 1));
+		assert_eq!((owned.excerpt.as_str(), owned.context.as_str()), ("", ""));
+	}
+}
+
+/// Test that single-line strings end at the end of their line, so a missing closing quote
+/// is reported on the line it's missing from.
+#[test]
+fn newline_in_string() {
+	for (source, string) in [
+		("a = \"one\n two\"\n", "\"one"),
+		("a = 'one\n two'\n", "'one"),
+		("a = \"unclosed\r\nb = \"x\"\r\n", "\"unclosed"),
+		("a = \"escaped \\\"\nb = 1\n", "\"escaped \\\""),
+		("\"quoted\nkey\" = 1\n", "\"quoted"),
+	] {
+		let err = Toml::parse(source).unwrap_err();
+		assert_eq!(err.kind, TomlErrorKind::UnclosedString, "{source}");
+		assert_eq!(err.src.as_str(), string, "{source}");
+		assert_eq!(err.line_col().0, 1, "{source}");
+		assert!(!err.is_truncation(), "{source}");
+	}
+
+	// The rest of the document is still parsed when recovering from the error
+	let (toml, errors) = Toml::parse_lenient("a = \"one\nb = 2\nc = 'x'\n");
+	assert_eq!(errors.len(), 1);
+	assert_eq!(toml.get_integer("b"), Ok(2));
+	assert_eq!(toml.get_string("c"), Ok("x"));
+
+	// Multiline strings can still contain newlines
+	let toml = Toml::parse("a = \"\"\"one\ntwo\"\"\"\nb = '''one\ntwo'''\n").unwrap();
+	assert_eq!(toml.get_string("a"), Ok("one\ntwo"));
+	assert_eq!(toml.get_string("b"), Ok("one\ntwo"));
+}
+
+/// Test that anything besides a comment after a key/value pair or table header, on the
+/// same line, is an error.
+#[test]
+fn trailing_content() {
+	for (source, token) in [
+		("a = 1 b = 2\n", "b"),
+		("flag = true \"oops\"\n", "\"oops\""),
+		("a = 'x' y\n", "y"),
+		("a = [1, 2] 3\n", "3"),
+		("a = { b = 1 } c\n", "c"),
+		("[table] key = 1\n", "key"),
+		("[[table]] x\n", "x"),
+		("[table]]\n", "]"),
+		("a = 1\rb = 2\n", "\r"),
+	] {
+		let err = Toml::parse(source).unwrap_err();
+		assert_eq!(
+			err.kind,
+			TomlErrorKind::ExpectedNewlineAfterValue,
+			"{source}"
+		);
+		assert_eq!(err.src.as_str(), token, "{source}");
+	}
+
+	// Whitespace and comments are fine
+	let toml = Toml::parse("a = 1 \t# one\r\n[table] # table\nb = 2\t").unwrap();
+	assert_eq!(toml.get_integer("a"), Ok(1));
+}
+
+/// Test that values and keys that start out valid, but continue into something else,
+/// point at the right thing.
+#[test]
+fn invalid_tokens() {
+	// Unquoted words get a hint to quote them
+	let err = Toml::parse("a = yes\nb = 1\n").unwrap_err();
+	assert_eq!(err.kind, TomlErrorKind::UnrecognisedValue);
+	assert_eq!(err.src.as_str(), "yes");
+	assert!(err
+		.to_string()
+		.ends_with("= help: string values must be quoted, like `\"yes\"`"));
+
+	// Numbers that run into letters are reported as a whole, instead of as a number
+	// followed by a key
+	let err = Toml::parse("a = 12ab\nb = 1\n").unwrap_err();
+	assert_eq!(err.kind, TomlErrorKind::UnrecognisedValue);
+	assert_eq!(err.src.as_str(), "12ab");
+	assert!(err.to_string().contains("like `\"12ab\"`"));
+	for (source, value) in [
+		("a = [1, 12ab]\n", "12ab"),
+		("a = { b = -1x }\n", "-1x"),
+		("a = trueish\n", "trueish"),
+		("a = infinity\n", "infinity"),
+	] {
+		let err = Toml::parse(source).unwrap_err();
+		assert_eq!(err.kind, TomlErrorKind::UnrecognisedValue, "{source}");
+		assert_eq!(err.src.as_str(), value, "{source}");
+	}
+
+	// Only things that look like words get the hint
+	for source in ["a = 1.5e\n", "a = 0x1g\n", "a = 🦀\n", "a = 'unclosed\n"] {
+		let err = Toml::parse(source).unwrap_err();
+		assert!(!err.to_string().contains("help:"), "{source}");
+	}
+
+	// Keys point at the invalid character
+	let err = Toml::parse("12ab! = 1\n").unwrap_err();
+	assert_eq!(err.kind, TomlErrorKind::InvalidBareKey);
+	assert_eq!(err.src.as_str(), "!");
+	assert_eq!(err.line_col(), (1, 5));
+	let err = Toml::parse("[table]\nkey🦀 = 1\n").unwrap_err();
+	assert_eq!(err.kind, TomlErrorKind::InvalidBareKey);
+	assert_eq!(err.src.as_str(), "🦀");
+	let err = Toml::parse("[ta$ble]\n").unwrap_err();
+	assert_eq!(err.kind, TomlErrorKind::InvalidBareKey);
+	assert_eq!(err.src.as_str(), "$");
+
+	// All-digit keys are still fine
+	let toml = Toml::parse("5678 = 12\n").unwrap();
+	assert_eq!(toml.get_integer("5678"), Ok(12));
+}
+
+/// Test that commas have to come between values: arrays can't have empty items, and
+/// inline tables can't have trailing commas.
+#[test]
+fn misplaced_commas() {
+	for (source, col) in [
+		("a = [1,,2]\n", 8),
+		("a = [,]\n", 6),
+		("a = [,1]\n", 6),
+		("a = [1, ,]\n", 9),
+		("a = [1,\n,2]\n", 1),
+		("a = {x = 1,}\n", 11),
+		("a = {x = 1, }\n", 11),
+		("a = {x = 1, y = [2,],}\n", 21),
+	] {
+		let err = Toml::parse(source).unwrap_err();
+		assert_eq!(err.kind, TomlErrorKind::UnrecognisedValue, "{source:?}");
+		assert_eq!(err.src.as_str(), ",", "{source:?}");
+		assert_eq!(err.line_col().1, col, "{source:?}");
+	}
+
+	// Trailing commas are fine in arrays, and commas are fine between values
+	for source in [
+		"a = [1,]\n",
+		"a = [1, # comment\n]\n",
+		"a = [\n1,\n2,\n]\n",
+		"a = []\n",
+		"a = {}\n",
+		"a = {x = 1, y = [2,]}\n",
+	] {
+		assert!(Toml::parse(source).is_ok(), "{source:?}");
+	}
+}
+
+/// Test that control characters in strings have to be escaped.
+#[test]
+fn control_characters() {
+	for (source, offset) in [
+		("a = \"bell\u{7}\"\n", 9),
+		("a = 'null\0'\n", 9),
+		("a = 'del\u{7f}'\n", 8),
+		("a = \"\"\"\nmulti\nline\u{1b}\"\"\"\n", 18),
+		("\"key\u{c}\" = 1\n", 4),
+		// Carriage returns are only allowed as part of a CRLF
+		("a = 'cr\rbad'\n", 7),
+		("a = '''\ncr\rbad'''\n", 10),
+		("a = \"cr\rbad\"\n", 7),
+		("a = \"\"\"\r\ncr\r\rbad\"\"\"\n", 11),
+		("a = '''cr\r'''\n", 9),
+	] {
+		let err = Toml::parse(source).unwrap_err();
+		assert_eq!(
+			err.kind,
+			TomlErrorKind::ControlCharacterInString,
+			"{source:?}"
+		);
+		assert_eq!((err.src.start, err.src.end), (offset, offset), "{source:?}");
+	}
+
+	// Tabs and line breaks are fine, and so are escaped control characters
+	let toml = Toml::parse(concat!(
+		"a = \"tab\tbell\\u0007\"\n",
+		"b = '''\r\ncrlf\r\nlines\n'''\n",
+		"c = \"\"\"\ttab\"\"\"\n",
+	))
+	.unwrap();
+	assert_eq!(toml.get_string("a"), Ok("tab\tbell\u{7}"));
+	assert_eq!(toml.get_string("b"), Ok("crlf\r\nlines\n"));
+	assert_eq!(toml.get_string("c"), Ok("\ttab"));
+}
+
+/// Test that CRLFs in multiline strings are kept as they're written, except right after
+/// the opening delimiter, where they're trimmed like an LF.
+#[test]
+fn multiline_crlf() {
+	let toml = Toml::parse(concat!(
+		"a = \"\"\"\r\none\r\ntwo\ntwo and a half\r\n\"\"\"\r\n",
+		"b = '''\r\none\r\ntwo'''\r\n",
+		"c = \"\"\"\r\n\"\"\"\r\n",
+		"d = '''\r\n'''\r\n",
+		"e = \"\"\"\r\n\r\nblank\"\"\"\r\n",
+		"f = \"\"\"escaped\\r\\n\r\n\"\"\"\r\n",
+	))
+	.unwrap();
+	assert_eq!(toml.get_string("a"), Ok("one\r\ntwo\ntwo and a half\r\n"));
+	assert_eq!(toml.get_string("b"), Ok("one\r\ntwo"));
+	assert_eq!(toml.get_string("c"), Ok(""));
+	assert_eq!(toml.get_string("d"), Ok(""));
+	assert_eq!(toml.get_string("e"), Ok("\r\nblank"));
+	assert_eq!(toml.get_string("f"), Ok("escaped\r\n\r\n"));
+
+	// A lone carriage return right after the delimiter isn't trimmed, it's an error
+	for source in ["a = \"\"\"\rone\"\"\"\n", "a = '''\rone'''\n"] {
+		let err = Toml::parse(source).unwrap_err();
+		assert_eq!(
+			err.kind,
+			TomlErrorKind::ControlCharacterInString,
+			"{source:?}"
+		);
+		assert_eq!(err.src.as_str(), "\r", "{source:?}");
+		assert_eq!(err.src.start, 7, "{source:?}");
+	}
+}
+
+/// Test that a line-ending backslash can only be followed by whitespace on its own line,
+/// and that other unknown escapes point at the escape.
+#[test]
+fn line_ending_backslash() {
+	for (source, escape) in [
+		("a = \"\"\"a \\  b\"\"\"\n", "\\  b"),
+		("a = \"\"\"a\\\tb\n\"\"\"\n", "\\\tb"),
+		("a = \"\"\"trailing\\  \"\"\"\n", "\\  "),
+		("a = \"single \\ line\"\n", "\\ l"),
+		("a = \"x\\q\"\n", "\\q"),
+	] {
+		let err = Toml::parse(source).unwrap_err();
+		assert_eq!(err.kind, TomlErrorKind::UnknownEscapeSequence, "{source:?}");
+		assert_eq!(err.src.as_str(), escape, "{source:?}");
+	}
+
+	let toml = Toml::parse(concat!(
+		"a = \"\"\"one \\\n   two\"\"\"\n",
+		"b = \"\"\"one \\  \t\n\n  \r\n   two\"\"\"\n",
+		"c = \"\"\"one \\\r\n two\"\"\"\n",
+		"d = \"\"\"one\\\n\"\"\"\n",
+	))
+	.unwrap();
+	assert_eq!(toml.get_string("a"), Ok("one two"));
+	assert_eq!(toml.get_string("b"), Ok("one two"));
+	assert_eq!(toml.get_string("c"), Ok("one two"));
+	assert_eq!(toml.get_string("d"), Ok("one"));
+}
+
+/// Test that multi-byte characters where the parser peeks at a fixed number of bytes are
+/// errors instead of panics, and that the errors can always be displayed.
+#[test]
+fn multibyte_characters() {
+	for (source, kind, span) in [
+		("a = tru🦀\n", TomlErrorKind::UnrecognisedValue, "tru🦀"),
+		("a = fals🦀\n", TomlErrorKind::UnrecognisedValue, "fals🦀"),
+		("a = tr🦀\n", TomlErrorKind::UnrecognisedValue, "tr🦀"),
+		("a = i🦀\n", TomlErrorKind::UnrecognisedValue, "i🦀"),
+		("a = n🦀\n", TomlErrorKind::UnrecognisedValue, "n🦀"),
+		("a = +🦀\n", TomlErrorKind::UnrecognisedValue, "🦀"),
+		("a = -é\n", TomlErrorKind::UnrecognisedValue, "é"),
+		("a = 1🦀\n", TomlErrorKind::UnrecognisedValue, "1🦀"),
+		("a = 1.5ü\n", TomlErrorKind::UnrecognisedValue, "1.5ü"),
+		("a = 0x🦀\n", TomlErrorKind::InvalidNumber, "0x🦀"),
+		(
+			"a = \"\\u12é4\"\n",
+			TomlErrorKind::UnknownUnicodeScalar,
+			"\\u12é",
+		),
+		(
+			"a = \"\\U0001F98é\"\n",
+			TomlErrorKind::UnknownUnicodeScalar,
+			"\\U0001F98é",
+		),
+		("a = [1 🦀]\n", TomlErrorKind::NoCommaDelimeter, "🦀"),
+		("a = { b = 1 🦀 }\n", TomlErrorKind::NoCommaDelimeter, "🦀"),
+		(
+			"a = true ü\n",
+			TomlErrorKind::ExpectedNewlineAfterValue,
+			"ü",
+		),
+		("[🦀]\n", TomlErrorKind::InvalidBareKey, "🦀"),
+		("a.🦀 = 1\n", TomlErrorKind::InvalidBareKey, "🦀"),
+		("12 ü\n", TomlErrorKind::NoEqualsInAssignment, "12 ü"),
+	] {
+		let err = Toml::parse(source).unwrap_err();
+		assert_eq!(err.kind, kind, "{source:?}");
+		assert_eq!(err.src.as_str(), span, "{source:?}");
+		assert!(!err.to_string().is_empty());
+		assert!(!err.to_owned_error().excerpt.is_empty());
+	}
+
+	// Unicode escapes can't have a sign, even though Rust's number parser allows one
+	let err = Toml::parse("a = \"\\u+041\"\n").unwrap_err();
+	assert_eq!(err.kind, TomlErrorKind::UnknownUnicodeScalar);
+	assert_eq!(err.src.as_str(), "\\u+041");
+
+	// Values that start like a keyword are still fine next to multi-byte characters
+	let toml = Toml::parse("a = [true,'🦀', inf,\"é\",nan]\n").unwrap();
+	assert_eq!(toml.get_array("a").unwrap().len(), 5);
+}
+
+/// Test that assignments without a key report the entire assignment.
+#[test]
+fn missing_key() {
+	let err = Toml::parse("= 5\n").unwrap_err();
+	assert_eq!(err.kind, TomlErrorKind::NoKeyInAssignment);
+	assert_eq!(err.src.as_str(), "= 5");
+
+	let err = Toml::parse("[table]\n.a = 1\r\nb = 2\r\n").unwrap_err();
+	assert_eq!(err.kind, TomlErrorKind::LeadingDotInKey);
+	assert_eq!(err.src.as_str(), ".a = 1");
+
+	let err = Toml::parse("a = 1\n  =\nb = 2\n").unwrap_err();
+	assert_eq!(err.kind, TomlErrorKind::NoKeyInAssignment);
+	assert_eq!(err.line_col(), (2, 3));
+	assert_eq!(err.src.as_str(), "=");
+}
+
+/// Test that owned errors keep the original error's information after the source is
+/// dropped.
+#[test]
+fn owned() {
+	fn parse(source: String) -> Result<(), Box<dyn std::error::Error>> {
+		Toml::parse(&source).map_err(OwnedTomlError::from)?;
+		Ok(())
+	}
+
+	let source = String::from("a = 1\nkey = nope\n");
+	let expected = Toml::parse(&source).unwrap_err().to_string();
+	let err = parse(source).unwrap_err();
+	assert_eq!(err.to_string(), expected);
+
+	let err = err.downcast::<OwnedTomlError>().unwrap();
+	assert_eq!(err.kind, TomlErrorKind::UnrecognisedValue);
+	assert_eq!((err.start, err.end), (12, 15));
+	assert_eq!(err.line_col, (2, 7));
+	assert_eq!(err.excerpt, "nope");
+	assert_eq!(err.context, "a = 1\nkey = nope");
+	assert_eq!(err.section, None);
+
+	// Context includes the lines before and after the error
+	let source = String::from("[table]\na = 1\r\nb = \"unclosed\r\nc = 3\r\nd = 4\r\n");
+	let err = Toml::parse(&source).unwrap_err().into_owned();
+	drop(source);
+	assert_eq!(err.excerpt, "\"unclosed");
+	assert_eq!(err.context, "a = 1\nb = \"unclosed\nc = 3");
+	assert_eq!(err.section.as_deref(), Some("[table]"));
+}
+
+/// Test that `parse_all` finds every error in a document, without reporting extra errors
+/// for the rest of a broken item.
+#[test]
+fn parse_all() {
+	let source = concat!(
+		"a = nope\n",
+		"b = 1\n",
+		"= 5\n",
+		".c = 1\n",
+		"  =\n",
+		"[broken\n",
+		"d = 1\n",
+		"[table]\n",
+		"e = { x = 1 y = [2, 3] }\n",
+		"f = [1,\n",
+		"  2 3,\n",
+		"  { g = 4 },\n",
+		"]\n",
+		"h = 'ok'\n",
+		"i = tru\n",
+	);
+	let errors = Toml::parse_all(source).unwrap_err();
+	let errors: Vec<_> = errors
+		.iter()
+		.map(|err| (err.kind, err.line_col().0))
+		.collect();
+	assert_eq!(
+		errors,
+		[
+			(TomlErrorKind::UnrecognisedValue, 1),
+			(TomlErrorKind::NoKeyInAssignment, 3),
+			(TomlErrorKind::LeadingDotInKey, 4),
+			(TomlErrorKind::NoKeyInAssignment, 5),
+			(TomlErrorKind::UnclosedBracket, 6),
+			(TomlErrorKind::NoCommaDelimeter, 9),
+			(TomlErrorKind::NoCommaDelimeter, 11),
+			(TomlErrorKind::UnrecognisedValue, 15),
+		]
+	);
+
+	// Valid documents parse normally
+	let toml = Toml::parse_all("a = 1\n[table]\nb = 2\n").unwrap();
+	assert_eq!(toml.get_table("table").unwrap().get_integer("b"), Ok(2));
+
+	// Errors from merging tables are reported, too
+	let errors = Toml::parse_all("a = 1\n[a]\nb = 2\n[c]\nd = nope\n").unwrap_err();
+	assert_eq!(errors.len(), 2);
+	assert_eq!(errors[0].kind, TomlErrorKind::ReusedKey);
+	assert_eq!(errors[1].section.unwrap().as_str(), "[c]");
+}
+
+/// Test that lenient parsing keeps everything that parsed successfully.
+#[test]
+fn lenient() {
+	let source = concat!(
+		"a = 1\n",
+		"b = nope\n",
+		"c = 3\n",
+		"[table.d]\n",
+		"x = 4\n",
+		"[broken\n",
+		"e = 6\n",
+		"[other]\n",
+		"f = [1, 2 3]\n",
+		"g = 'ok'\n",
+		"[table]\n",
+		"d = 7\n",
+		"h = 8\n",
+	);
+	let (toml, errors) = Toml::parse_lenient(source);
+	assert_eq!(errors.len(), 4);
+
+	assert_eq!(toml.get_integer("a"), Ok(1));
+	assert!(toml.get("b").is_none());
+	assert_eq!(toml.get_integer("c"), Ok(3));
+
+	// Reused keys keep their first value, but the rest of the table is still inserted
+	let table = toml.get_table("table").unwrap();
+	assert_eq!(table.get_table("d").unwrap().get_integer("x"), Ok(4));
+	assert_eq!(table.get_integer("h"), Ok(8));
+
+	// Tables with broken headers are skipped
+	assert!(toml.get("broken").is_none());
+	assert!(toml.get("e").is_none());
+
+	let other = toml.get_table("other").unwrap();
+	assert!(other.get("f").is_none());
+	assert_eq!(other.get_string("g"), Ok("ok"));
+
+	// Valid documents have no errors
+	let (toml, errors) = Toml::parse_lenient("a = 1\n");
+	assert!(errors.is_empty());
+	assert_eq!(toml.get_integer("a"), Ok(1));
+}
+
+/// Test that every kind of error has a unique, stable code that can be looked up again.
+#[test]
+fn codes() {
+	for (idx, kind) in TomlErrorKind::ALL.into_iter().enumerate() {
+		let code = kind.code();
+		let number = format!("E{:04}", idx + 1);
+		assert!(code.starts_with(&format!("{number}-")), "{code}");
+		assert_eq!(TomlErrorKind::from_code(code), Some(kind));
+		assert_eq!(TomlErrorKind::from_code(&number), Some(kind));
+	}
+	assert_eq!(TomlErrorKind::from_code("E0000"), None);
+	assert_eq!(TomlErrorKind::from_code("reused-key"), None);
+	assert_eq!(TomlErrorKind::from_code(""), None);
+
+	assert_eq!(TomlErrorKind::ReusedKey.code(), "E0009-reused-key");
+	assert_eq!(
+		TomlErrorKind::UnknownEscapeSequence.category(),
+		TomlErrorCategory::Strings
+	);
+	assert_eq!(
+		TomlErrorKind::NumberTooLarge.category(),
+		TomlErrorCategory::Numbers
+	);
+	assert_eq!(
+		TomlErrorKind::ReusedKey.category(),
+		TomlErrorCategory::Structure
+	);
+
+	// Codes are shown in error messages
+	let err = Toml::parse(
+		"a = 1
+a = 2
+[a]
+",
+	)
+	.unwrap_err();
+	assert!(err.to_string().starts_with("error[E0009-reused-key]: "));
+}
@@ -0,0 +1,123 @@
+//! Exercises each of boml's optional features, and the ones that work together, under
+//! whichever features are enabled. Every combination of features should build and pass:
+//!
+//! ```sh
+//! cargo test --no-default-features
+//! cargo test --features miette
+//! cargo test --features serde
+//! cargo test --features toml-compat
+//! cargo test --features miette,serde
+//! cargo test --features miette,toml-compat
+//! cargo test --features serde,toml-compat
+//! cargo test --all-features
+//! ```
+//!
+//! None of the features are incompatible with each other, so there aren't any
+//! combinations that are expected to fail to compile.
+
+use boml::prelude::*;
+
+const SOURCE: &str = "[package]\nname = 'boml'\nversion = \"0.3.1\"\nrust-version = 1.77\n";
+
+/// Test the parts of boml that don't need any features.
+#[test]
+fn core() {
+	let toml = Toml::parse(SOURCE).unwrap();
+	let package = toml.get_table("package").unwrap();
+	assert_eq!(package.get_string("name"), Ok("boml"));
+	assert_eq!(package.get_float("rust-version"), Ok(1.77));
+
+	let err = Toml::parse("a = 'unclosed\n").unwrap_err();
+	assert_eq!(err.kind, TomlErrorKind::UnclosedString);
+	assert!(err.to_owned_error().to_string().contains("E0007"));
+}
+
+#[cfg(feature = "miette")]
+#[test]
+fn miette() {
+	use miette::Diagnostic;
+
+	let err = Toml::parse("a = 'unclosed\n").unwrap_err();
+	let diagnostic = err.with_file_name("Cargo.toml").into_diagnostic();
+	assert_eq!(
+		diagnostic.code().unwrap().to_string(),
+		"E0007-unclosed-string"
+	);
+	assert_eq!(diagnostic.labels().unwrap().count(), 1);
+}
+
+#[cfg(feature = "serde")]
+#[derive(Debug, PartialEq, serde::Deserialize)]
+struct Manifest<'a> {
+	#[serde(borrow)]
+	package: Package<'a>,
+}
+#[cfg(feature = "serde")]
+#[derive(Debug, PartialEq, serde::Deserialize)]
+#[serde(rename_all = "kebab-case")]
+struct Package<'a> {
+	name: &'a str,
+	version: String,
+	rust_version: f64,
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn serde() {
+	let manifest: Manifest = boml::serde::from_str(SOURCE).unwrap();
+	assert_eq!(manifest.package.name, "boml");
+	assert_eq!(manifest.package.version, "0.3.1");
+
+	let err = boml::serde::from_str::<Manifest>("[package]\nname = 'boml'\n").unwrap_err();
+	assert!(err.to_string().contains("version"));
+}
+
+#[cfg(feature = "toml-compat")]
+#[test]
+fn toml_compat() {
+	let toml = Toml::parse(SOURCE).unwrap();
+	let converted: toml::Table = (&*toml).try_into().unwrap();
+	assert_eq!(converted["package"]["name"].as_str(), Some("boml"));
+
+	let back = TomlTable::try_from(&converted).unwrap();
+	assert_eq!(&back, &*toml);
+}
+
+/// Test deserializing tables that were converted from the `toml` crate, which don't
+/// borrow from a source.
+#[cfg(all(feature = "serde", feature = "toml-compat"))]
+#[test]
+fn serde_with_toml_compat() {
+	#[derive(Debug, PartialEq, serde::Deserialize)]
+	struct OwnedManifest {
+		package: OwnedPackage,
+	}
+	#[derive(Debug, PartialEq, serde::Deserialize)]
+	struct OwnedPackage {
+		name: String,
+		version: String,
+	}
+
+	let converted: toml::Table = toml::from_str(SOURCE).unwrap();
+	let table = TomlTable::try_from(&converted).unwrap();
+	let manifest: OwnedManifest = boml::serde::from_table(&table).unwrap();
+	assert_eq!(manifest.package.name, "boml");
+	assert_eq!(manifest.package.version, "0.3.1");
+
+	// Borrowed strings can't be deserialized from tables that don't borrow a source
+	assert!(boml::serde::from_table::<Manifest>(&table).is_err());
+}
+
+/// Test that errors from parsing TOML for serde still have everything needed to show
+/// them as diagnostics.
+#[cfg(all(feature = "miette", feature = "serde"))]
+#[test]
+fn miette_with_serde() {
+	let Err(boml::serde::Error::Parse(err)) = boml::serde::from_str::<Manifest>("a = [1") else {
+		panic!("expected a parse error");
+	};
+	assert_eq!(err.kind, TomlErrorKind::UnclosedBracket);
+
+	let diagnostic = Toml::parse("a = [1").unwrap_err().into_diagnostic();
+	assert_eq!(diagnostic.to_string(), err.kind.to_string());
+}
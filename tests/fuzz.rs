@@ -0,0 +1,107 @@
+//! Inputs that used to crash or hang the parser, found by the fuzzer in `fuzz/`. Each one
+//! should be an error, not a panic.
+
+use boml::prelude::*;
+
+/// Every input the fuzzer has found a crash or hang for, minimized.
+const CRASHERS: &[&str] = &[
+	// Documents ending where a key was expected
+	"[",
+	"[[",
+	"[ ",
+	"[\"\".",
+	"[a.",
+	"_.",
+	"1.5.",
+	"a.b. ",
+	"{a.",
+	"a = {b.",
+	// Dates and times, which can't be parsed yet
+	"xa = 12-",
+	"a = 07:32:00",
+	"a = 1979-05-27T07:32:00Z",
+	"a = [2021-01-01]",
+	// Comments at the end of an array that never ends
+	"a = [#",
+	"a = [1, # comment",
+	"a = [\n#",
+	// Multi-byte characters where the parser peeked at a fixed number of bytes
+	"a = tru🦀",
+	"a = fals🦀",
+	"a = i🦀",
+	"a = n🦀",
+	"a = 0x🦀",
+	"a = \"\\u12é4\"",
+	"a = \"\\U0001F98é\"",
+	"''ü",
+	"[🦀]",
+	"\u{FEFF}[",
+];
+
+#[test]
+fn crashers() {
+	for source in CRASHERS {
+		let err = Toml::parse(source).unwrap_err();
+		// Rendering the error shouldn't panic either
+		assert!(!err.to_string().is_empty(), "{source:?}");
+		assert!(!err.to_owned_error().excerpt.is_empty(), "{source:?}");
+
+		let (_, errors) = Toml::parse_lenient(source);
+		assert!(!errors.is_empty(), "{source:?}");
+		for err in errors {
+			assert!(!err.to_string().is_empty(), "{source:?}");
+		}
+
+		let options = ParseOptions::new().toml_version(TomlVersion::V1_1);
+		assert!(Toml::parse_with(source, &options).is_err(), "{source:?}");
+		assert!(TomlDocument::parse(source).is_err(), "{source:?}");
+	}
+}
+
+/// Test the errors for inputs that used to crash, now that they don't.
+#[test]
+fn crasher_errors() {
+	for (source, kind, span) in [
+		("[", TomlErrorKind::UnclosedBracket, "["),
+		("[[ ", TomlErrorKind::UnclosedBracket, "[[ "),
+		("_.", TomlErrorKind::NoValueInAssignment, "."),
+		("1.5.", TomlErrorKind::NoValueInAssignment, "."),
+		("[a. ", TomlErrorKind::NoValueInAssignment, " "),
+		("xa = 12-", TomlErrorKind::UnsupportedDateTime, "12-"),
+		(
+			"a = 07:32:00\n",
+			TomlErrorKind::UnsupportedDateTime,
+			"07:32:00",
+		),
+		("a = [#", TomlErrorKind::UnclosedBracket, "[#"),
+	] {
+		let err = Toml::parse(source).unwrap_err();
+		assert_eq!(err.kind, kind, "{source:?}");
+		assert_eq!(err.src.as_str(), span, "{source:?}");
+	}
+}
+
+/// Test that strings with lots of escaped quotes don't overflow the stack. Finding the end
+/// of a basic string used to recurse once for every escaped quote.
+#[test]
+fn escaped_quotes() {
+	const QUOTES: usize = 1_000_000;
+
+	for (source, expected) in [
+		(
+			format!("a = \"{}\"", "\\\"".repeat(QUOTES)),
+			"\"".repeat(QUOTES),
+		),
+		(
+			format!("a = \"\"\"{}\"\"\"", "\\\"".repeat(QUOTES)),
+			"\"".repeat(QUOTES),
+		),
+		(
+			format!("a = \"\"\"{}\"\"\"", "\\\"\"\"".repeat(QUOTES)),
+			"\"\"\"".repeat(QUOTES),
+		),
+	] {
+		let toml = Toml::parse(&source).unwrap();
+		assert_eq!(toml.get_string("a"), Ok(expected.as_str()));
+	}
+}
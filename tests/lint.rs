@@ -0,0 +1,71 @@
+use boml::prelude::*;
+
+/// Test that each kind of suspicious key pair is found, with the right spans.
+#[test]
+fn suspicious_keys() {
+	let source = concat!(
+		"max_retries = 3\n",
+		"max-retries = 5\n",
+		"\n",
+		"[server]\n",
+		"Timeout = 10\n",
+		"timeout = 20\n",
+		"\n",
+		"[client]\n",
+		"port = 80\n",
+		"\n",
+		"[database]\n",
+		"port = 5432\n",
+	);
+	let toml = Toml::parse(source).unwrap();
+	let pairs = toml.suspicious_key_pairs();
+	assert_eq!(pairs.len(), 3);
+
+	let (first, second, reason) = &pairs[0];
+	assert_eq!(*reason, SimilarityReason::DashUnderscore);
+	assert_eq!(first.to_string(), "max_retries");
+	assert_eq!(second.to_string(), "max-retries");
+	assert_eq!(second.span.line_col(), (2, 1));
+
+	let (first, second, reason) = &pairs[1];
+	assert_eq!(*reason, SimilarityReason::CaseOnly);
+	assert_eq!(first.keys, ["server", "Timeout"]);
+	assert_eq!(second.keys, ["server", "timeout"]);
+
+	let (first, second, reason) = &pairs[2];
+	assert_eq!(*reason, SimilarityReason::DuplicateInSiblingSections);
+	assert_eq!(first.to_string(), "client.port");
+	assert_eq!(first.span.line_col(), (9, 1));
+	assert_eq!(second.to_string(), "database.port");
+	assert_eq!(second.span.line_col(), (12, 1));
+
+	let warnings = toml.lint();
+	assert_eq!(warnings.len(), 3);
+	assert_eq!(
+		warnings[1].to_string(),
+		"`server.Timeout` (line 5, column 1) and `server.timeout` (line 6, column 1) only \
+		 differ in case"
+	);
+}
+
+/// Test that documents without suspicious keys don't produce warnings, even if arrays of
+/// tables repeat keys.
+#[test]
+fn clean() {
+	let source = concat!(
+		"name = 'boml'\n",
+		"\n",
+		"[server]\n",
+		"host = 'localhost'\n",
+		"port = 80\n",
+		"\n",
+		"[[bin]]\n",
+		"name = 'first'\n",
+		"\n",
+		"[[bin]]\n",
+		"name = 'second'\n",
+	);
+	let toml = Toml::parse(source).unwrap();
+	assert!(toml.suspicious_key_pairs().is_empty());
+	assert!(toml.lint().is_empty());
+}
@@ -0,0 +1,289 @@
+use boml::prelude::*;
+
+/// Parses `a = <literal>` and returns the value of `a`.
+fn parse(literal: &str) -> Result<f64, TomlErrorKind> {
+	let source = format!("a = {literal}\n");
+	let toml = Toml::parse(&source).map_err(|err| err.kind)?;
+	match toml.get("a").unwrap() {
+		TomlValue::Integer(int) => Ok(*int as f64),
+		TomlValue::Float(float) => Ok(*float),
+		other => panic!("{literal} parsed as {other:?}"),
+	}
+}
+
+/// Checks that each integer literal parses to the expected value.
+fn assert_integers(cases: &[(&str, i64)]) {
+	for (literal, expected) in cases {
+		let source = format!("a = {literal}\n");
+		let toml = Toml::parse(&source).unwrap_or_else(|err| panic!("{literal}: {err}"));
+		assert_eq!(toml.get_integer("a"), Ok(*expected), "{literal}");
+	}
+}
+
+/// Checks that each literal is rejected with the expected error, and that the error
+/// covers the whole literal.
+fn assert_errors(cases: &[(&str, TomlErrorKind)]) {
+	for (literal, kind) in cases {
+		let source = format!("a = {literal}\n");
+		let err = Toml::parse(&source).unwrap_err();
+		assert_eq!(err.kind, *kind, "{literal}");
+		assert!(
+			literal.ends_with(err.src.as_str()),
+			"{literal}: {:?}",
+			err.src
+		);
+	}
+}
+
+/// Test the largest and smallest integers in every base.
+#[test]
+fn integer_limits() {
+	assert_integers(&[
+		("9223372036854775807", i64::MAX),
+		("-9223372036854775808", i64::MIN),
+		("+9223372036854775807", i64::MAX),
+		("9_223_372_036_854_775_807", i64::MAX),
+		("0x7FFFFFFFFFFFFFFF", i64::MAX),
+		("0x7fff_ffff_ffff_ffff", i64::MAX),
+		("0o777777777777777777777", i64::MAX),
+		(
+			// 63 digits
+			"0b111111111111111111111111111111111111111111111111111111111111111",
+			i64::MAX,
+		),
+		("0x0", 0),
+		("0o0", 0),
+		("0b0", 0),
+		("0x00000000000000000000001", 1),
+		("-0", 0),
+		("+0", 0),
+	]);
+}
+
+/// Test that integers outside of an i64's range are errors in every base, instead of
+/// wrapping around.
+#[test]
+fn integer_overflow() {
+	assert_errors(&[
+		("9223372036854775808", TomlErrorKind::NumberTooLarge),
+		("-9223372036854775809", TomlErrorKind::NumberTooLarge),
+		("99999999999999999999999", TomlErrorKind::NumberTooLarge),
+		("0x8000000000000000", TomlErrorKind::NumberTooLarge),
+		("0xFFFFFFFFFFFFFFFF", TomlErrorKind::NumberTooLarge),
+		("0o1000000000000000000000", TomlErrorKind::NumberTooLarge),
+		("0o1777777777777777777777", TomlErrorKind::NumberTooLarge),
+		(
+			// 64 digits
+			"0b1000000000000000000000000000000000000000000000000000000000000000",
+			TomlErrorKind::NumberTooLarge,
+		),
+		(
+			"0b1111111111111111111111111111111111111111111111111111111111111111",
+			TomlErrorKind::NumberTooLarge,
+		),
+	]);
+}
+
+/// Test that the decimal and radix paths reject the same malformed numbers.
+#[test]
+fn invalid_digits() {
+	assert_errors(&[
+		// Digits that don't belong to the base
+		("0b102", TomlErrorKind::InvalidNumber),
+		("0o78", TomlErrorKind::InvalidNumber),
+		("0x12G", TomlErrorKind::InvalidNumber),
+		("12a", TomlErrorKind::UnrecognisedValue),
+		// Prefixes without digits
+		("0x", TomlErrorKind::InvalidNumber),
+		("0o", TomlErrorKind::InvalidNumber),
+		("0b", TomlErrorKind::InvalidNumber),
+		// Leading zeros
+		("0123", TomlErrorKind::NumberHasInvalidBaseOrLeadingZero),
+		("00", TomlErrorKind::NumberHasInvalidBaseOrLeadingZero),
+		("-01", TomlErrorKind::NumberHasInvalidBaseOrLeadingZero),
+		("01.5", TomlErrorKind::NumberHasInvalidBaseOrLeadingZero),
+		("0_1", TomlErrorKind::NumberHasInvalidBaseOrLeadingZero),
+	]);
+}
+
+/// Test that floats need digits on both sides of their decimal point, and in their
+/// exponent.
+#[test]
+fn malformed_floats() {
+	assert_errors(&[
+		(".5", TomlErrorKind::InvalidNumber),
+		("-.5", TomlErrorKind::InvalidNumber),
+		("1.", TomlErrorKind::InvalidNumber),
+		("-1.", TomlErrorKind::InvalidNumber),
+		("1.e5", TomlErrorKind::InvalidNumber),
+		("3.e+20", TomlErrorKind::InvalidNumber),
+		("1e", TomlErrorKind::InvalidNumber),
+		("1e+", TomlErrorKind::InvalidNumber),
+		("1.5E-", TomlErrorKind::InvalidNumber),
+		// Not numbers at all
+		(".", TomlErrorKind::UnrecognisedValue),
+		(".e5", TomlErrorKind::UnrecognisedValue),
+	]);
+
+	// Floats in arrays and inline tables are checked, too
+	let err = Toml::parse("a = [1.0, .5]\n").unwrap_err();
+	assert_eq!(err.kind, TomlErrorKind::InvalidNumber);
+	assert_eq!(err.src.as_str(), ".5");
+	let err = Toml::parse("a = { b = 2.e3 }\n").unwrap_err();
+	assert_eq!(err.kind, TomlErrorKind::InvalidNumber);
+	assert_eq!(err.src.as_str(), "2.e3");
+
+	for (literal, expected) in [
+		("1e5", 1e5),
+		("1E+5", 1e5),
+		("1.5e-3", 1.5e-3),
+		("0e0", 0.0),
+	] {
+		assert_eq!(parse(literal), Ok(expected), "{literal}");
+	}
+}
+
+/// Test that digits that don't belong to a number's base are errors that point at the
+/// first bad digit, in every base.
+#[test]
+fn radix_digits() {
+	let cases = [
+		("0b2", 2),
+		("0b102", 4),
+		("0b1_1_2", 6),
+		("0o8", 2),
+		("0o99", 2),
+		("0o7_78", 5),
+		("0xg", 2),
+		("0xFF_GG", 5),
+		("0x12G", 4),
+		("0xz", 2),
+	];
+	for (literal, offset) in cases {
+		let source = format!("a = {literal}\n");
+		let err = Toml::parse(&source).unwrap_err();
+		assert_eq!(err.kind, TomlErrorKind::InvalidNumber, "{literal}");
+		assert_eq!(err.src.start, 4 + offset, "{literal}");
+		assert_eq!(err.src.len(), 1, "{literal}");
+	}
+
+	// Empty digit sequences, even at the end of the file
+	for source in ["a = 0x\n", "a = 0o # comment\n", "a = 0b"] {
+		let err = Toml::parse(source).unwrap_err();
+		assert_eq!(err.kind, TomlErrorKind::InvalidNumber, "{source}");
+	}
+
+	assert_integers(&[("0xFF", 255), ("0xff", 255), ("0o17", 15), ("0b1010", 10)]);
+}
+
+/// Test that only decimal integers can have a sign.
+#[test]
+fn signed_radix() {
+	assert_errors(&[
+		("-0x1", TomlErrorKind::NumberHasInvalidBaseOrLeadingZero),
+		("+0x10", TomlErrorKind::NumberHasInvalidBaseOrLeadingZero),
+		("-0o7", TomlErrorKind::NumberHasInvalidBaseOrLeadingZero),
+		("+0o7", TomlErrorKind::NumberHasInvalidBaseOrLeadingZero),
+		("+0b1", TomlErrorKind::NumberHasInvalidBaseOrLeadingZero),
+		("-0b1_0", TomlErrorKind::NumberHasInvalidBaseOrLeadingZero),
+	]);
+
+	// The error covers the sign
+	let err = Toml::parse("a = -0xFF\n").unwrap_err();
+	assert_eq!(err.src.as_str(), "-0xFF");
+}
+
+/// Test that underscores are only allowed between two digits, and that errors point at
+/// the misplaced underscore.
+#[test]
+fn underscores() {
+	let cases = [
+		("1__2", 1),
+		("5_", 1),
+		("1_.0", 1),
+		("1._0", 2),
+		("1e_5", 2),
+		("1_e5", 1),
+		("1.5e+_3", 5),
+		("0x_1", 2),
+		("0x1_", 3),
+		("0b1__0", 3),
+		("0o7_", 3),
+		("-1__0", 2),
+	];
+	for (literal, offset) in cases {
+		let source = format!("a = {literal}\n");
+		let err = Toml::parse(&source).unwrap_err();
+		assert_eq!(err.kind, TomlErrorKind::InvalidNumber, "{literal}");
+		assert_eq!(err.src.as_str(), "_", "{literal}");
+		assert_eq!(err.src.start, 4 + offset, "{literal}");
+	}
+
+	// Underscores can't start a number, since then it's a bare word
+	assert_errors(&[("_5", TomlErrorKind::UnrecognisedValue)]);
+
+	assert_integers(&[
+		("1_000", 1000),
+		("-1_0", -10),
+		("0xdead_beef", 0xdead_beef),
+		("0o7_7", 0o77),
+		("0b1_0_1", 0b101),
+	]);
+	assert_eq!(parse("1_0.0_1e1_0"), Ok(10.01e10));
+}
+
+/// Test the largest, smallest, and most precise floats.
+#[test]
+fn float_limits() {
+	let cases = [
+		("1.7976931348623157e308", f64::MAX),
+		("-1.7976931348623157e308", f64::MIN),
+		("2.2250738585072014e-308", f64::MIN_POSITIVE),
+		// Subnormals
+		("5e-324", 5e-324),
+		("4.9406564584124654e-324", 5e-324),
+		("2.225073858507201e-308", 2.225073858507201e-308),
+		// Too small to represent, so it's rounded to 0
+		("1e-400", 0.0),
+		("0.0", 0.0),
+		("0e0", 0.0),
+		("6.626_070_15e-34", 6.62607015e-34),
+	];
+	for (literal, expected) in cases {
+		assert_eq!(parse(literal), Ok(expected), "{literal}");
+	}
+
+	// The sign of zero is kept
+	assert!(parse("-0.0").unwrap().is_sign_negative());
+	assert!(parse("+0.0").unwrap().is_sign_positive());
+}
+
+/// Test that floats too big for an f64 are errors, instead of silently becoming infinity.
+#[test]
+fn float_overflow() {
+	assert_errors(&[
+		("1.8e308", TomlErrorKind::NumberTooLarge),
+		("-1.8e308", TomlErrorKind::NumberTooLarge),
+		("1e1000", TomlErrorKind::NumberTooLarge),
+	]);
+	assert_eq!(parse("inf"), Ok(f64::INFINITY));
+	assert_eq!(parse("-inf"), Ok(f64::NEG_INFINITY));
+}
+
+/// Test that dates and times can't have a sign, and that signed numbers that look a bit
+/// like them still work.
+#[test]
+fn signed_dates() {
+	assert_errors(&[
+		("-2021-01-01", TomlErrorKind::SignedDateTime),
+		("+2021-01-01", TomlErrorKind::SignedDateTime),
+		("+07:30:00", TomlErrorKind::SignedDateTime),
+		("-07:32:00.999", TomlErrorKind::SignedDateTime),
+		("+1979-05-27T07:32:00Z", TomlErrorKind::SignedDateTime),
+	]);
+	let err = Toml::parse("a = -2021-01-01\n").unwrap_err();
+	assert_eq!(err.src.as_str(), "-2021-01-01");
+
+	assert_integers(&[("-12", -12), ("+1234", 1234), ("-2021", -2021)]);
+	assert_eq!(parse("-1e-5"), Ok(-1e-5));
+}
@@ -0,0 +1,196 @@
+use boml::prelude::*;
+
+/// Test that `\e` and `\xHH` escapes are only allowed in TOML 1.1, in every kind of basic
+/// string.
+#[test]
+fn toml_1_1_escapes() {
+	let v1_1 = ParseOptions::new().toml_version(TomlVersion::V1_1);
+	let cases = [
+		("a = \"\\e[0m\"\n", "\u{1b}[0m", "\\e"),
+		("a = \"\\x41\\xfF\\x00\"\n", "A\u{ff}\0", "\\x"),
+		("a = \"\"\"\nline\\x0A\\e\"\"\"\n", "line\n\u{1b}", "\\x"),
+	];
+	for (source, expected, escape) in cases {
+		let toml = Toml::parse_with(source, &v1_1).unwrap();
+		assert_eq!(toml.get_string("a"), Ok(expected), "{source:?}");
+
+		for err in [
+			Toml::parse(source).unwrap_err(),
+			Toml::parse_with(source, &ParseOptions::default()).unwrap_err(),
+		] {
+			assert_eq!(err.kind, TomlErrorKind::UnknownEscapeSequence, "{source:?}");
+			assert_eq!(err.src.as_str(), escape, "{source:?}");
+		}
+	}
+
+	// Quoted keys, too
+	let toml = Toml::parse_with("\"\\x41\" = 1\n", &v1_1).unwrap();
+	assert_eq!(toml.get_integer("A"), Ok(1));
+	assert!(Toml::parse("\"\\x41\" = 1\n").is_err());
+
+	// `\x` needs exactly 2 hex digits
+	for (source, escape) in [
+		("a = \"\\x4\"\n", "\\x4"),
+		("a = \"\\x\"\n", "\\x"),
+		("a = \"\\x4g\"\n", "\\x4g"),
+		("a = \"\\x+1\"\n", "\\x+1"),
+		("a = \"\\xé1\"\n", "\\xé1"),
+	] {
+		let err = Toml::parse_with(source, &v1_1).unwrap_err();
+		assert_eq!(err.kind, TomlErrorKind::UnknownUnicodeScalar, "{source:?}");
+		assert_eq!(err.src.as_str(), escape, "{source:?}");
+		let err = Toml::parse(source).unwrap_err();
+		assert_eq!(err.kind, TomlErrorKind::UnknownEscapeSequence, "{source:?}");
+	}
+	// Only 2 digits are part of the escape
+	let toml = Toml::parse_with("a = \"\\x414\"\n", &v1_1).unwrap();
+	assert_eq!(toml.get_string("a"), Ok("A4"));
+
+	// Literal strings don't have escapes in any version
+	let toml = Toml::parse_with("a = '\\e\\x41'\n", &v1_1).unwrap();
+	assert_eq!(toml.get_string("a"), Ok("\\e\\x41"));
+}
+
+/// Test that parsing with the default options is the same as [`Toml::parse()`], for
+/// valid and invalid documents.
+#[test]
+fn default_options() {
+	assert_eq!(ParseOptions::new(), ParseOptions::default());
+	assert_eq!(ParseOptions::new().toml_version, TomlVersion::V1_0);
+
+	let options = ParseOptions::new();
+	for source in [
+		"a = 1\n[table]\nb = 'two'\n[[array]]\nc = [3.0, { d = true }]\n",
+		"\u{FEFF}\"\" = \"\\u00e9\"\n",
+		"a = \"\\e\"\n",
+		"a = 1\na = 2\n",
+		"[table\n",
+	] {
+		let results = [
+			Toml::parse(source),
+			Toml::parse_with(source, &options),
+			boml::parse_with(source, &options),
+		];
+		match results {
+			[Ok(first), Ok(second), Ok(third)] => {
+				assert_eq!(*first, *second, "{source:?}");
+				assert_eq!(*first, *third, "{source:?}");
+			}
+			[Err(first), Err(second), Err(third)] => {
+				assert_eq!(first.to_string(), second.to_string(), "{source:?}");
+				assert_eq!(first.to_string(), third.to_string(), "{source:?}");
+				assert_eq!(first.src.start, second.src.start, "{source:?}");
+			}
+			results => panic!("{source:?}: {results:?}"),
+		}
+	}
+}
+
+/// Test that deeply nested documents are errors instead of overflowing the stack, and
+/// that every kind of nesting counts towards the limit.
+#[test]
+fn max_depth() {
+	let default_limit = ParseOptions::DEFAULT_MAX_DEPTH;
+	assert_eq!(ParseOptions::new().max_depth, default_limit);
+
+	// Pathological documents, which used to overflow the stack
+	let keys = vec!["a"; 100_000].join(".");
+	for source in [
+		format!("a = {}", "[".repeat(100_000)),
+		format!("a = {}1{}", "[".repeat(100_000), "]".repeat(100_000)),
+		format!("a = {}", "{ b = ".repeat(100_000)),
+		format!("{keys} = 1"),
+		format!("[{keys}]"),
+		format!("[[{keys}]]"),
+	] {
+		let err = Toml::parse(&source).unwrap_err();
+		assert_eq!(err.kind, TomlErrorKind::NestingTooDeep);
+		assert!(err.src.start < 10 * default_limit);
+	}
+
+	// Strings aren't nested, but lots of escaped quotes used to overflow the stack too
+	let quotes = "\\\"".repeat(100_000);
+	for source in [
+		format!("a = \"{quotes}\""),
+		format!("a = \"\"\"{quotes}\"\"\""),
+		format!("a = [\"{quotes}\"]"),
+	] {
+		assert!(Toml::parse(&source).is_ok());
+	}
+	for source in [format!("a = \"{quotes}"), format!("a = \"\"\"{quotes}")] {
+		let err = Toml::parse(&source).unwrap_err();
+		assert_eq!(err.kind, TomlErrorKind::UnclosedString);
+	}
+
+	// Documents right at the limit are fine
+	let source = format!(
+		"a = {}1{}",
+		"[".repeat(default_limit - 1),
+		"]".repeat(default_limit - 1)
+	);
+	assert!(Toml::parse(&source).is_ok());
+	assert!(Toml::parse(&format!("[{}]\n", vec!["a"; default_limit].join("."))).is_ok());
+
+	// Key parts, arrays, inline tables, and arrays of tables each count as a level
+	let options = ParseOptions::new().max_depth(3);
+	for source in [
+		"a = [[1]]\n",
+		"a = [[1], [2], {}]\n",
+		"a = { b = 1, c = 2, d = 3 }\n",
+		"a.b.c = 1\n",
+		"[a.b]\nc = 1\n",
+		"[[a]]\nb = 1\n",
+		"[a.b]\n[a]\nc.d = 1\n",
+	] {
+		assert!(Toml::parse_with(source, &options).is_ok(), "{source:?}");
+	}
+	for (source, offset) in [
+		("a = [[[1]]]\n", 6),
+		("a = { b = { c = 1 } }\n", 10),
+		("a = { b.c = 1 }\n", 8),
+		("a = [{ b = [1] }]\n", 7),
+		("a.b.c.d = 1\n", 6),
+		("[a.b.c.d]\n", 7),
+		("[[a.b.c]]\n", 0),
+		("[a.b]\nc = [1]\n", 10),
+		("[[a.b]]\nc = 1\n", 8),
+	] {
+		let err = Toml::parse_with(source, &options).unwrap_err();
+		assert_eq!(err.kind, TomlErrorKind::NestingTooDeep, "{source:?}");
+		assert_eq!(err.src.start, offset, "{source:?}");
+		assert!(Toml::parse(source).is_ok(), "{source:?}");
+	}
+
+	// Recovering from the error doesn't throw off the depth of the rest of the document
+	let source = format!("a = {}1{}\nb = [[1]]\n", "[".repeat(1000), "]".repeat(1000));
+	let (toml, errors) = Toml::parse_lenient(&source);
+	assert_eq!(errors.len(), 1);
+	assert_eq!(errors[0].kind, TomlErrorKind::NestingTooDeep);
+	assert!(toml.get_array("b").is_ok());
+}
+
+/// Test that the error-recovering parsers use the options they're given.
+#[test]
+fn recovering_with_options() {
+	let source = "a = \"\\e\"\nb = [[1]]\nc = 1\n";
+	let v1_1 = ParseOptions::new().toml_version(TomlVersion::V1_1);
+
+	let (toml, errors) = Toml::parse_lenient(source);
+	assert_eq!(errors.len(), 1);
+	assert_eq!(errors[0].kind, TomlErrorKind::UnknownEscapeSequence);
+	assert!(toml.get("a").is_none());
+	assert!(Toml::parse_all(source).is_err());
+
+	let (toml, errors) = Toml::parse_lenient_with(source, &v1_1);
+	assert!(errors.is_empty());
+	assert_eq!(toml.get_string("a"), Ok("\u{1b}"));
+	assert!(Toml::parse_all_with(source, &v1_1).is_ok());
+
+	let shallow = v1_1.max_depth(1);
+	let (toml, errors) = Toml::parse_lenient_with(source, &shallow);
+	assert_eq!(errors.len(), 1);
+	assert_eq!(errors[0].kind, TomlErrorKind::NestingTooDeep);
+	assert_eq!(toml.get_integer("c"), Ok(1));
+	let errors = Toml::parse_all_with(source, &shallow).unwrap_err();
+	assert_eq!(errors.len(), 1);
+}
@@ -0,0 +1,76 @@
+use {boml::prelude::*, std::path::PathBuf};
+
+/// A file in the temporary directory, which is deleted when it's dropped.
+struct TempFile(PathBuf);
+impl TempFile {
+	fn new(name: &str, contents: &[u8]) -> Self {
+		let path = std::env::temp_dir().join(format!("boml-{}-{name}", std::process::id()));
+		std::fs::write(&path, contents).unwrap();
+		Self(path)
+	}
+}
+impl Drop for TempFile {
+	fn drop(&mut self) {
+		let _ = std::fs::remove_file(&self.0);
+	}
+}
+
+fn load(path: &std::path::Path) -> Result<OwnedToml, LoadError> {
+	OwnedToml::parse_file(path)
+}
+
+/// Test that documents can be returned from the function that read them.
+#[test]
+fn parse_file() {
+	let file = TempFile::new("valid.toml", b"[package]\nname = \"boml\"\n");
+	let owned = load(&file.0).unwrap();
+
+	let toml = owned.toml();
+	let package = toml.get_table("package").unwrap();
+	assert_eq!(package.get_string("name"), Ok("boml"));
+	assert_eq!(owned.source(), "[package]\nname = \"boml\"\n");
+	assert_eq!(owned.clone().into_source(), owned.source());
+}
+
+/// Test that parsing errors name the file, and that missing or non-UTF-8 files are IO
+/// errors.
+#[test]
+fn load_errors() {
+	let file = TempFile::new("invalid.toml", b"a = 1\na = 2\n");
+	let Err(LoadError::Parse(err)) = load(&file.0) else {
+		panic!("Expected a parsing error");
+	};
+	assert_eq!(err.kind, TomlErrorKind::ReusedKey);
+	assert_eq!(err.line_col, (2, 1));
+	assert!(err
+		.to_string()
+		.contains(&format!("--> {}:2:1", file.0.display())));
+
+	let file = TempFile::new("binary.toml", b"a = \"\xff\"\n");
+	let err = load(&file.0).unwrap_err();
+	assert!(matches!(&err, LoadError::Io(err) if err.kind() == std::io::ErrorKind::InvalidData));
+	assert!(err.to_string().starts_with("failed to read TOML: "));
+
+	let missing = file.0.with_extension("missing");
+	let err = load(&missing).unwrap_err();
+	assert!(matches!(&err, LoadError::Io(err) if err.kind() == std::io::ErrorKind::NotFound));
+	assert!(std::error::Error::source(&err).is_some());
+}
+
+/// Test that owned documents can be created from strings, and keep their options.
+#[test]
+fn parse_strings() {
+	let owned = OwnedToml::parse(String::from("a = 1")).unwrap();
+	assert_eq!(owned.toml().get_integer("a"), Ok(1));
+
+	let Err(LoadError::Parse(err)) = OwnedToml::parse("a = ") else {
+		panic!("Expected a parsing error");
+	};
+	assert_eq!(err.kind, TomlErrorKind::NoValueInAssignment);
+
+	let source = "escape = \"\\e\"";
+	assert!(OwnedToml::parse(source).is_err());
+	let options = ParseOptions::new().toml_version(TomlVersion::V1_1);
+	let owned = OwnedToml::parse_with(source, &options).unwrap();
+	assert_eq!(owned.toml().get_string("escape"), Ok("\u{1b}"));
+}
@@ -48,6 +48,31 @@ fn quoted_keys() {
 	);
 }
 
+/// Test that empty quoted keys are allowed, and that `""` and `''` are the same key.
+#[test]
+fn empty_keys() {
+	let toml = Toml::parse(concat!(
+		"\"\" = 1\n",
+		"a.''.b = 2\n",
+		"[table.\"\" . x]\n",
+		"y = 3\n",
+		"[[array.'']]\n",
+		"z = 4\n",
+	))
+	.unwrap();
+	assert_eq!(toml.get_integer(""), Ok(1));
+	assert_eq!(toml.get_path("a.\"\".b"), Ok(&TomlValue::Integer(2)));
+	assert_eq!(toml.get_path("a.''.b"), Ok(&TomlValue::Integer(2)));
+	assert_eq!(toml.get_path("table.''.x.y"), Ok(&TomlValue::Integer(3)));
+	let array = toml.get_table("array").unwrap().get_array("").unwrap();
+	assert_eq!(array[0].table().unwrap().get_integer("z"), Ok(4));
+
+	let err = Toml::parse("\"\" = 1\n'' = true\n").unwrap_err();
+	assert_eq!(err.kind, TomlErrorKind::ReusedKey);
+	assert_eq!(err.line_col(), (2, 2));
+	assert!(err.src.is_empty());
+}
+
 /// Test that boml can handle dotted keys.
 #[test]
 fn dotted_keys() {
@@ -88,7 +113,12 @@ fn basic_strings() {
 		"multi = \"\"\"me when\\n",
 		"i do multiline\\r pretty neat",
 		"\"\"\"\n",
-		"whitespace = \"\"\"white\\    \n\n\n\r\n    space\"\"\""
+		"whitespace = \"\"\"white\\    \n\n\n\r\n    space\"\"\"\n",
+		r#"unicode = "\u00e9t\u00E9 🦀 \U0001F980" "#,
+		"\n",
+		r#"edges = "\\é\\" "#,
+		"\n",
+		"trailing = \"\"\"line\\\n\"\"\""
 	);
 	let toml = Toml::parse(toml_source).unwrap();
 	toml.assert_strings(vec![
@@ -97,6 +127,9 @@ fn basic_strings() {
 		("escapes", "\t\n\r\\"),
 		("multi", "me when\ni do multiline\r pretty neat"),
 		("whitespace", "whitespace"),
+		("unicode", "été 🦀 🦀"),
+		("edges", "\\é\\"),
+		("trailing", "line"),
 	]);
 }
 
@@ -108,8 +141,6 @@ fn integers() {
 		"decimal = 10\n",
 		"octal = 0o10\n",
 		"binary = 0b10\n",
-		"neghex = -0x10\n",
-		"posoctal = +0o10\n",
 		"lmao = -0\n",
 		"underscore = 10_00\n",
 		"single = 2\n",
@@ -121,8 +152,6 @@ fn integers() {
 			("decimal", 10),
 			("octal", 8),
 			("binary", 2),
-			("neghex", -16),
-			("posoctal", 8),
 			("lmao", 0),
 			("underscore", 1000),
 			("single", 2),
@@ -345,3 +374,104 @@ impl TomlTestUtils for Toml<'_> {
 		}
 	}
 }
+
+/// Test that a byte order mark at the start of a document is skipped, without throwing
+/// off error positions, and that one anywhere else is still an error.
+#[test]
+fn byte_order_mark() {
+	let toml = Toml::parse("\u{FEFF}[package]\nname = \"boml\"\n").unwrap();
+	assert_eq!(
+		toml.get_table("package").unwrap().get_string("name"),
+		Ok("boml")
+	);
+	let toml = Toml::parse("\u{FEFF}a = 1\r\n").unwrap();
+	assert_eq!(toml.get_integer("a"), Ok(1));
+	assert!(Toml::parse("\u{FEFF}").unwrap().is_empty());
+
+	// Positions line up with what an editor shows, which doesn't include the mark
+	let err = Toml::parse("\u{FEFF}a = nope\n").unwrap_err();
+	assert_eq!(err.line_col(), (1, 5));
+	assert_eq!(err.line_text(), "a = nope");
+	assert_eq!(err.src.as_str(), "nope");
+	assert!(err.to_string().contains("1 | a = nope\n  |     ^^^^"));
+	let err = Toml::parse("\u{FEFF}a = 1\nb = nope\n").unwrap_err();
+	assert_eq!(err.line_col(), (2, 5));
+
+	for (source, line_col) in [
+		("a = 1\n\u{FEFF}b = 2\n", (2, 1)),
+		("\u{FEFF}\u{FEFF}a = 1\n", (1, 1)),
+		("a\u{FEFF} = 1\n", (1, 2)),
+	] {
+		let err = Toml::parse(source).unwrap_err();
+		assert_eq!(err.kind, TomlErrorKind::InvalidBareKey, "{source:?}");
+		assert_eq!(err.src.as_str(), "\u{FEFF}", "{source:?}");
+		assert_eq!(err.line_col(), line_col, "{source:?}");
+	}
+
+	// It's just a character in strings
+	let toml = Toml::parse("a = '\u{FEFF}'\n").unwrap();
+	assert_eq!(toml.get_string("a"), Ok("\u{FEFF}"));
+}
+
+/// Arrays and tables are allocated based on a rough guess of their size. Test that values
+/// the guess gets wrong are still parsed correctly.
+#[test]
+fn capacity_estimates() {
+	let toml = Toml::parse(concat!(
+		"empty = []\n",
+		"spaced = [ \n ]\n",
+		"strings = ['a,b,c', \"]\", 'd']\n",
+		"nested = [[1, 2, 3], [4], [], {a = 1, b = 2}]\n",
+		"trailing = [1, 2, 3,]\n",
+		"comments = [ # a, b, c\n 1, # ]\n 2]\n",
+		"table = { a = ',,,', b.c = 1, b.d = [1, 2] }\n",
+		"[[item]]\n",
+		"a = 1\n",
+		"b = 2\n",
+		"[[item]]\n",
+		"a = 3\n",
+		"[[item]]\n",
+		"a = 4\n",
+		"b = 5\n",
+		"c = 6\n",
+	))
+	.unwrap();
+
+	let len = |key| toml.get_array(key).unwrap().len();
+	assert_eq!(len("empty"), 0);
+	assert_eq!(len("spaced"), 0);
+	assert_eq!(len("strings"), 3);
+	assert_eq!(len("nested"), 4);
+	assert_eq!(len("trailing"), 3);
+	assert_eq!(len("comments"), 2);
+	assert_eq!(toml.get_table("table").unwrap().len(), 2);
+
+	let items = toml.get_array("item").unwrap();
+	let lens: Vec<_> = items
+		.iter()
+		.map(|item| item.table().unwrap().len())
+		.collect();
+	assert_eq!(lens, [2, 1, 3]);
+
+	// Nested arrays don't all count the same commas
+	let toml = Toml::parse("a = [[[['x,,,,,,,,,,,,,,,,']]]]\n").unwrap();
+	let mut value = toml.get("a").unwrap();
+	for _ in 0..3 {
+		let TomlValue::Array(array) = value else {
+			panic!("expected an array, found {value:?}");
+		};
+		assert!(array.capacity() < 16, "{}", array.capacity());
+		value = &array[0];
+	}
+
+	// Commas in strings and comments aren't counted
+	let toml = Toml::parse(concat!(
+		"a = ['x,,,,,,,,,,,,,,,,', # ,,,,,,,,,,,,,,,,\n",
+		"  \"\\\",,,,,,,,,,,,,,,,\"]\n",
+	))
+	.unwrap();
+	let Some(TomlValue::Array(array)) = toml.get("a") else {
+		panic!("expected an array");
+	};
+	assert_eq!(array.capacity(), 2);
+}
@@ -0,0 +1,233 @@
+#![cfg(feature = "serde")]
+
+use {
+	boml::{prelude::*, serde::Error},
+	serde::Deserialize,
+	std::collections::{BTreeMap, HashMap},
+};
+
+#[derive(Debug, Deserialize, PartialEq)]
+struct Package<'a> {
+	name: &'a str,
+	version: String,
+	authors: Vec<String>,
+	edition: Option<u16>,
+	publish: Option<bool>,
+	metadata: Metadata,
+}
+
+#[derive(Debug, Deserialize, PartialEq)]
+struct Metadata {
+	ratio: f32,
+	tags: HashMap<String, i8>,
+	nested: Vec<Vec<u8>>,
+}
+
+/// Test nested structs, options, collections, and number conversions.
+#[test]
+fn nested_structs() {
+	let source = concat!(
+		"name = 'boml'\n",
+		"version = \"0.3.1\"\n",
+		"authors = ['BrightShard', \"Someone\\tElse\"]\n",
+		"edition = 2021\n",
+		"[metadata]\n",
+		"ratio = 1\n",
+		"tags = { a = -1, b = 127 }\n",
+		"nested = [[1, 2], [], [3]]\n",
+	);
+	let package: Package = boml::serde::from_str(source).unwrap();
+	assert_eq!(
+		package,
+		Package {
+			name: "boml",
+			version: "0.3.1".to_string(),
+			authors: vec!["BrightShard".to_string(), "Someone\tElse".to_string()],
+			edition: Some(2021),
+			publish: None,
+			metadata: Metadata {
+				ratio: 1.0,
+				tags: HashMap::from([("a".to_string(), -1), ("b".to_string(), 127)]),
+				nested: vec![vec![1, 2], vec![], vec![3]],
+			},
+		}
+	);
+
+	// Deserializing from an already-parsed table or value works the same way
+	let toml = Toml::parse(source).unwrap();
+	let metadata: Metadata = boml::serde::from_value(toml.get("metadata").unwrap()).unwrap();
+	assert_eq!(metadata, package.metadata);
+	let package: Package = boml::serde::from_table(&toml).unwrap();
+	assert_eq!(package.name, "boml");
+}
+
+#[derive(Debug, Deserialize, PartialEq)]
+enum External {
+	Unit,
+	Newtype(i64),
+	Tuple(i64, String),
+	Struct { a: bool },
+}
+
+#[derive(Debug, Deserialize, PartialEq)]
+#[serde(tag = "type")]
+enum Internal {
+	Http { port: u16 },
+	Unix { path: String },
+}
+
+#[derive(Debug, Deserialize, PartialEq)]
+#[serde(tag = "t", content = "c")]
+enum Adjacent {
+	Number(i64),
+	Text(String),
+}
+
+#[derive(Debug, Deserialize, PartialEq)]
+#[serde(untagged)]
+enum Untagged {
+	Number(i64),
+	Text(String),
+	List(Vec<Untagged>),
+}
+
+#[derive(Debug, Deserialize, PartialEq)]
+struct Enums {
+	external: Vec<External>,
+	internal: Vec<Internal>,
+	adjacent: Vec<Adjacent>,
+	untagged: Vec<Untagged>,
+}
+
+/// Test enums in each of serde's representations.
+#[test]
+fn enums() {
+	let source = concat!(
+		"external = [\n",
+		"  'Unit',\n",
+		"  { Newtype = 1 },\n",
+		"  { Tuple = [2, 'two'] },\n",
+		"  { Struct = { a = true } },\n",
+		"]\n",
+		"internal = [\n",
+		"  { type = 'Http', port = 80 },\n",
+		"  { type = 'Unix', path = '/tmp/socket' },\n",
+		"]\n",
+		"adjacent = [{ t = 'Number', c = 1 }, { t = 'Text', c = 'one' }]\n",
+		"untagged = [1, 'one', [2, ['two']]]\n",
+	);
+	let enums: Enums = boml::serde::from_str(source).unwrap();
+	assert_eq!(
+		enums,
+		Enums {
+			external: vec![
+				External::Unit,
+				External::Newtype(1),
+				External::Tuple(2, "two".to_string()),
+				External::Struct { a: true },
+			],
+			internal: vec![
+				Internal::Http { port: 80 },
+				Internal::Unix {
+					path: "/tmp/socket".to_string()
+				},
+			],
+			adjacent: vec![Adjacent::Number(1), Adjacent::Text("one".to_string())],
+			untagged: vec![
+				Untagged::Number(1),
+				Untagged::Text("one".to_string()),
+				Untagged::List(vec![
+					Untagged::Number(2),
+					Untagged::List(vec![Untagged::Text("two".to_string())]),
+				]),
+			],
+		}
+	);
+
+	// Externally tagged enums need exactly one key
+	let err = boml::serde::from_str::<HashMap<String, External>>("a = { Unit = 1, Newtype = 2 }")
+		.unwrap_err();
+	assert!(
+		err.to_string().starts_with("`a`: invalid length 2"),
+		"{err}"
+	);
+}
+
+#[derive(Debug, Deserialize, PartialEq)]
+struct Flattened {
+	name: String,
+	#[serde(flatten)]
+	server: Server,
+	#[serde(flatten)]
+	rest: BTreeMap<String, i64>,
+}
+
+#[derive(Debug, Deserialize, PartialEq)]
+struct Server {
+	host: String,
+	port: u16,
+}
+
+/// Test that flattened fields are taken from the same table.
+#[test]
+fn flatten() {
+	let source = "name = 'web'\nhost = 'localhost'\nport = 8080\nworkers = 4\nretries = 2\n";
+	let flattened: Flattened = boml::serde::from_str(source).unwrap();
+	assert_eq!(
+		flattened,
+		Flattened {
+			name: "web".to_string(),
+			server: Server {
+				host: "localhost".to_string(),
+				port: 8080,
+			},
+			rest: BTreeMap::from([("retries".to_string(), 2), ("workers".to_string(), 4)]),
+		}
+	);
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+#[allow(dead_code)]
+struct Strict {
+	port: u16,
+}
+
+/// Test that errors say where the value that caused them is.
+#[test]
+fn errors() {
+	let err = boml::serde::from_str::<Package>("name = ").unwrap_err();
+	let Error::Parse(ref parse_err) = err else {
+		panic!("Expected a parsing error, got {err:?}");
+	};
+	assert_eq!(parse_err.kind, TomlErrorKind::NoValueInAssignment);
+
+	let err = boml::serde::from_str::<HashMap<String, Vec<Strict>>>(
+		"servers = [{ port = 80 }, { port = 70000 }]",
+	)
+	.unwrap_err();
+	assert_eq!(
+		err.to_string(),
+		"`servers[1].port`: invalid value: integer `70000`, expected u16"
+	);
+
+	let err =
+		boml::serde::from_str::<HashMap<String, Strict>>("[server]\npotr = 80\n").unwrap_err();
+	let Error::Custom { path, message } = err else {
+		panic!("Expected a custom error");
+	};
+	assert_eq!(path.as_deref(), Some("server.potr"));
+	assert!(message.starts_with("unknown field `potr`"), "{message}");
+
+	let err = boml::serde::from_str::<Strict>("").unwrap_err();
+	assert_eq!(err.to_string(), "missing field `port`");
+
+	// Borrowed strings can't have escapes
+	let err = boml::serde::from_str::<HashMap<&str, &str>>("a = \"\\n\"").unwrap_err();
+	assert!(
+		err.to_string().starts_with("`a`: invalid type: string"),
+		"{err}"
+	);
+	let borrowed: HashMap<&str, &str> = boml::serde::from_str("'a' = \"b\"").unwrap();
+	assert_eq!(borrowed["a"], "b");
+}
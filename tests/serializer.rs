@@ -0,0 +1,212 @@
+use boml::{
+	prelude::*,
+	serializer::{string_to_toml, EscapeStyle},
+};
+
+const STYLES: [EscapeStyle; 3] = [
+	EscapeStyle::Minimal,
+	EscapeStyle::AlwaysBasic,
+	EscapeStyle::AsciiOnly,
+];
+
+/// Fragments that are known to be tricky to escape correctly. The round-trip test glues
+/// these together in every possible order.
+const FRAGMENTS: &[&str] = &[
+	"a", " ", "'", "\"", "\\", "\n", "\r", "\r\n", "\t", "'''", "\"\"\"", "\u{7}", "\u{7F}", "é",
+	"😀",
+];
+
+/// Test that the minimal style picks the expected kind of string.
+#[test]
+fn minimal_style() {
+	let style = EscapeStyle::Minimal;
+	assert_eq!(string_to_toml("hello", style), "'hello'");
+	assert_eq!(string_to_toml("", style), "''");
+	assert_eq!(string_to_toml("C:\\path", style), "'C:\\path'");
+	assert_eq!(string_to_toml("it's", style), "\"it's\"");
+	assert_eq!(string_to_toml("bell\u{7}", style), "\"bell\\u0007\"");
+	assert_eq!(string_to_toml("two\nlines", style), "'''\ntwo\nlines'''");
+	assert_eq!(
+		string_to_toml("two\nlines, 'quoted'", style),
+		"\"\"\"\ntwo\nlines, 'quoted'\"\"\""
+	);
+}
+
+/// Test that the basic styles always produce single-line basic strings.
+#[test]
+fn basic_styles() {
+	assert_eq!(
+		string_to_toml("hello", EscapeStyle::AlwaysBasic),
+		"\"hello\""
+	);
+	assert_eq!(
+		string_to_toml("a\nb\t\"c\"", EscapeStyle::AlwaysBasic),
+		"\"a\\nb\\t\\\"c\\\"\""
+	);
+	assert_eq!(string_to_toml("é😀", EscapeStyle::AlwaysBasic), "\"é😀\"");
+	assert_eq!(
+		string_to_toml("é😀", EscapeStyle::AsciiOnly),
+		"\"\\u00E9\\U0001F600\""
+	);
+}
+
+/// Test that every combination of tricky fragments parses back to the original string,
+/// in every escape style.
+#[test]
+fn round_trip() {
+	let mut strings = vec![String::new()];
+	for first in FRAGMENTS {
+		strings.push(first.to_string());
+		for second in FRAGMENTS {
+			strings.push(format!("{first}{second}"));
+			for third in FRAGMENTS {
+				strings.push(format!("{first}{second}{third}"));
+			}
+		}
+	}
+
+	for string in &strings {
+		for style in STYLES {
+			let serialized = string_to_toml(string, style);
+			if style == EscapeStyle::AsciiOnly {
+				assert!(serialized.is_ascii(), "{serialized:?} isn't ASCII");
+			}
+
+			let source = format!("value = {serialized}\n");
+			let toml = Toml::parse(&source)
+				.unwrap_or_else(|err| panic!("Failed to parse {source:?}: {err:?}"));
+			assert_eq!(
+				toml.get_string("value").unwrap(),
+				string,
+				"{style:?} round trip failed for {source:?}"
+			);
+		}
+	}
+}
+
+/// Test that a large document written one section at a time parses back to the same
+/// structure.
+#[test]
+fn writer_large_document() {
+	let mut writer = TomlWriter::new(Vec::new());
+	writer.key_value("name", "generated").unwrap();
+	for idx in 0..1000 {
+		if idx % 2 == 0 {
+			writer
+				.begin_table(&format!("servers.server-{idx}"))
+				.unwrap();
+			writer.key_value("port", idx).unwrap();
+			writer.key_value("label", "it's \"quoted\"").unwrap();
+		} else {
+			writer.begin_array_of_tables("workers").unwrap();
+			writer.key_value("id", idx).unwrap();
+			writer.key_value("weight", idx as f64 / 2.0).unwrap();
+		}
+		writer.end().unwrap();
+	}
+
+	let output = String::from_utf8(writer.into_inner()).unwrap();
+	let toml = Toml::parse(&output).unwrap();
+	assert_eq!(toml.get_string("name"), Ok("generated"));
+
+	let servers = toml.get_table("servers").unwrap();
+	assert_eq!(servers.len(), 500);
+	let server = servers.get_table("server-998").unwrap();
+	assert_eq!(server.get_integer("port"), Ok(998));
+	assert_eq!(server.get_string("label"), Ok("it's \"quoted\""));
+
+	let workers = toml.get_array("workers").unwrap();
+	assert_eq!(workers.len(), 500);
+	let last = workers[499].table().unwrap();
+	assert_eq!(last.get_integer("id"), Ok(999));
+	assert_eq!(last.get_float("weight"), Ok(499.5));
+}
+
+/// Test that defining a table twice is an error when it's written, and that nothing is
+/// written for it.
+#[test]
+fn writer_duplicate_header() {
+	let mut writer = TomlWriter::new(Vec::new());
+	writer.begin_table("a.b").unwrap();
+	writer.begin_table("a").unwrap();
+	assert!(matches!(
+		writer.begin_table("a.b"),
+		Err(WriteError::DuplicateTable)
+	));
+	assert!(matches!(
+		writer.begin_array_of_tables("a"),
+		Err(WriteError::DuplicateTable)
+	));
+
+	let output = String::from_utf8(writer.into_inner()).unwrap();
+	assert_eq!(output, "[a.b]\n\n[a]\n");
+}
+
+/// Test the rest of the structural rules the writer checks.
+#[test]
+fn writer_rules() {
+	let mut writer = TomlWriter::new(Vec::new());
+	writer.key_value("value", 1).unwrap();
+	writer.key_value("inline", TomlTable::default()).unwrap();
+	assert!(matches!(
+		writer.key_value("value", 2),
+		Err(WriteError::DuplicateKey)
+	));
+	assert!(matches!(
+		writer.begin_table("value"),
+		Err(WriteError::DuplicateKey)
+	));
+	assert!(matches!(
+		writer.begin_table("inline.x"),
+		Err(WriteError::NotATable)
+	));
+	assert!(matches!(
+		writer.begin_table("a..b"),
+		Err(WriteError::InvalidPath)
+	));
+	assert!(matches!(
+		writer.key_value("time", TomlValue::LocalTime),
+		Err(WriteError::UnsupportedValue)
+	));
+
+	// Sub-tables defined by headers are keys in their parent table
+	writer.begin_table("parent.child").unwrap();
+	writer.begin_table("parent").unwrap();
+	assert!(matches!(
+		writer.key_value("child", 1),
+		Err(WriteError::DuplicateKey)
+	));
+
+	// Keys can only be written in an open section
+	writer.end().unwrap();
+	assert!(matches!(
+		writer.key_value("x", 1),
+		Err(WriteError::NoSection)
+	));
+	assert!(matches!(writer.end(), Err(WriteError::NoSection)));
+
+	// Each table in an array of tables starts empty
+	writer.begin_array_of_tables("items").unwrap();
+	writer.key_value("x", 1).unwrap();
+	writer.begin_array_of_tables("items").unwrap();
+	writer.key_value("x", 2).unwrap();
+	writer.begin_table("items.sub").unwrap();
+
+	let output = String::from_utf8(writer.into_inner()).unwrap();
+	let toml = Toml::parse(&output).unwrap();
+	assert_eq!(toml.get_array("items").unwrap().len(), 2);
+	assert_eq!(
+		output,
+		concat!(
+			"value = 1\n",
+			"inline = {}\n",
+			"\n[parent.child]\n",
+			"\n[parent]\n",
+			"\n[[items]]\n",
+			"x = 1\n",
+			"\n[[items]]\n",
+			"x = 2\n",
+			"\n[items.sub]\n",
+		)
+	);
+}
@@ -0,0 +1,602 @@
+use boml::prelude::*;
+
+/// Parses `headers`, and checks that it produces the same tree as `expected`, which
+/// should be written with inline tables.
+fn assert_tree(headers: &str, expected: &str) {
+	let parsed = Toml::parse(headers).unwrap();
+	let expected = Toml::parse(expected).unwrap();
+	assert_eq!(*parsed, *expected, "{headers}");
+}
+
+/// Test that headers inside arrays of tables are added to the right element of the array,
+/// no matter what order they're in.
+#[test]
+fn array_of_tables_interleaving() {
+	// Each `[[a]]` starts a new element, so the `[[a.b]]`s go in different elements
+	assert_tree(
+		"[[a]]\n[[a.b]]\nx = 1\n[[a]]\n[[a.b]]\nx = 2\n",
+		"a = [{ b = [{ x = 1 }] }, { b = [{ x = 2 }] }]\n",
+	);
+	// Same for plain tables
+	assert_tree(
+		"[[a]]\n[a.b]\nx = 1\n[[a]]\n[a.b]\nx = 2\n",
+		"a = [{ b = { x = 1 } }, { b = { x = 2 } }]\n",
+	);
+	// Multiple `[[a.b]]`s in one element, then a new element without any
+	assert_tree(
+		"[[a]]\nx = 1\n[[a.b]]\ny = 1\n[[a.b]]\ny = 2\n[[a]]\nx = 2\n",
+		"a = [{ x = 1, b = [{ y = 1 }, { y = 2 }] }, { x = 2 }]\n",
+	);
+	// An array of tables inside a normal table
+	assert_tree(
+		"[a]\nx = 1\n[[a.b]]\ny = 1\n[[a.b]]\ny = 2\n",
+		"a = { x = 1, b = [{ y = 1 }, { y = 2 }] }\n",
+	);
+	// Deeply nested arrays of tables, with an unrelated table in between
+	assert_tree(
+		"[[a]]\n[[a.b]]\n[[a.b.c]]\nx = 1\n[other]\n[[a.b.c]]\nx = 2\n[[a.b]]\n[[a]]\n",
+		"a = [{ b = [{ c = [{ x = 1 }, { x = 2 }] }, {}] }, {}]\nother = {}\n",
+	);
+	// Implicit tables between an array of tables and its sub-tables
+	assert_tree(
+		"[[a]]\n[a.b.c]\nx = 1\n[[a.b.d]]\n[[a]]\n[a.b.c]\nx = 2\n",
+		"a = [{ b = { c = { x = 1 }, d = [{}] } }, { b = { c = { x = 2 } } }]\n",
+	);
+}
+
+/// Test the spec's example of sub-tables in arrays of tables, and Cargo's `[[bin]]`
+/// targets with `[bin.metadata]` tables. Each sub-table belongs to the most recent
+/// element of its array.
+#[test]
+fn array_of_tables_subtables() {
+	let toml = Toml::parse(concat!(
+		"[[fruits]]\n",
+		"name = \"apple\"\n",
+		"\n",
+		"[fruits.physical]  # subtable\n",
+		"color = \"red\"\n",
+		"shape = \"round\"\n",
+		"\n",
+		"[[fruits.varieties]]  # nested array of tables\n",
+		"name = \"red delicious\"\n",
+		"\n",
+		"[[fruits.varieties]]\n",
+		"name = \"granny smith\"\n",
+		"\n",
+		"\n",
+		"[[fruits]]\n",
+		"name = \"banana\"\n",
+		"\n",
+		"[[fruits.varieties]]\n",
+		"name = \"plantain\"\n",
+	))
+	.unwrap();
+	let fruits = toml.get_array("fruits").unwrap();
+	assert_eq!(fruits.len(), 2);
+
+	let apple = fruits[0].table().unwrap();
+	assert_eq!(apple.get_string("name"), Ok("apple"));
+	let physical = apple.get_table("physical").unwrap();
+	assert_eq!(physical.get_string("color"), Ok("red"));
+	assert_eq!(physical.get_string("shape"), Ok("round"));
+	let varieties = apple.get_array("varieties").unwrap();
+	assert_eq!(varieties.len(), 2);
+	assert_eq!(
+		varieties[0].table().unwrap().get_string("name"),
+		Ok("red delicious")
+	);
+	assert_eq!(
+		varieties[1].table().unwrap().get_string("name"),
+		Ok("granny smith")
+	);
+
+	let banana = fruits[1].table().unwrap();
+	assert_eq!(banana.get_string("name"), Ok("banana"));
+	assert!(banana.get("physical").is_none());
+	let varieties = banana.get_array("varieties").unwrap();
+	assert_eq!(varieties.len(), 1);
+	assert_eq!(
+		varieties[0].table().unwrap().get_string("name"),
+		Ok("plantain")
+	);
+
+	assert_tree(
+		concat!(
+			"[[bin]]\nname = 'a'\n[bin.metadata]\nx = 1\n",
+			"[[bin]]\nname = 'b'\n[bin.metadata]\nx = 2\n[bin.metadata.deep]\ny = 3\n",
+		),
+		"bin = [{ name = 'a', metadata = { x = 1 } }, { name = 'b', metadata = { x = 2, deep = { y = 3 } } }]\n",
+	);
+	// Dotted keys in an element can be extended by headers under that element
+	assert_tree(
+		"[[a]]\nb.c = 1\n[a.b.d]\n[[a]]\nb.c = 2\n",
+		"a = [{ b = { c = 1, d = {} } }, { b = { c = 2 } }]\n",
+	);
+
+	// The sub-table is still defined once per element
+	let err = Toml::parse("[[a]]\n[a.b]\n[[a]]\n[a.b]\n[a.b]\n").unwrap_err();
+	assert_eq!(err.kind, TomlErrorKind::ReusedKey);
+	assert_eq!(err.line_col(), (5, 4));
+}
+
+/// Test that headers that conflict with existing values are errors.
+#[test]
+fn array_of_tables_conflicts() {
+	let cases = [
+		// Dotted keys before the first `[[a]]` make `a` a normal table
+		("a.x = 1\n[[a]]\n", "a"),
+		// A normal table can't become an array of tables, or vice versa
+		("[a]\n[[a]]\n", "a"),
+		("[[a]]\n[a]\n", "a"),
+		("[[a]]\n[a.b]\n[[a.b]]\n", "b"),
+		// Static arrays can't be extended
+		("a = [1, 2]\n[[a]]\n", "a"),
+		("a = []\n[[a]]\n", "a"),
+		// Values that aren't tables can't have sub-tables
+		("a = 1\n[a.b]\n", "a"),
+		("[[a]]\nb = 1\n[[a.b]]\n", "b"),
+	];
+
+	for (source, key) in cases {
+		let err = Toml::parse(source).unwrap_err();
+		assert_eq!(err.kind, TomlErrorKind::ReusedKey, "{source}");
+		assert_eq!(err.src.as_str(), key, "{source}");
+		assert!(err.section.is_some(), "{source}");
+	}
+}
+
+/// Test that arrays of tables can only be extended by `[[table]]` headers, and that they
+/// can't be redefined as plain tables (or vice versa), even inside other arrays of tables.
+#[test]
+fn array_of_tables_redefinition() {
+	let cases = [
+		("[[fruit]]\n[fruit]\n", "fruit"),
+		("[fruit]\n[[fruit]]\n", "fruit"),
+		("fruit = []\n[[fruit]]\n", "fruit"),
+		("fruit = [{ name = 'apple' }]\n[[fruit]]\n", "fruit"),
+		("fruit = [{ name = 'apple' }]\n[fruit]\n", "fruit"),
+		("[[fruit]]\n[[fruit.variety]]\n[fruit.variety]\n", "variety"),
+		("[[fruit]]\n[fruit.variety]\n[[fruit.variety]]\n", "variety"),
+		("[[fruit]]\nvariety = [{}]\n[[fruit.variety]]\n", "variety"),
+	];
+
+	for (source, key) in cases {
+		let err = Toml::parse(source).unwrap_err();
+		assert_eq!(err.kind, TomlErrorKind::ReusedKey, "{source}");
+		assert_eq!(err.src.as_str(), key, "{source}");
+	}
+
+	// The array keeps its original elements when recovering from the error
+	let (toml, errors) =
+		Toml::parse_lenient("fruit = [{ name = 'apple' }]\n[[fruit]]\nname = 'banana'\n");
+	assert_eq!(errors.len(), 1);
+	let fruit = toml.get_array("fruit").unwrap();
+	assert_eq!(fruit.len(), 1);
+	assert_eq!(fruit[0].table().unwrap().get_string("name"), Ok("apple"));
+}
+
+/// Test the ways the spec allows a table to be added to after it's defined.
+#[test]
+fn redefinition_allowed() {
+	// Defining a super-table after its sub-table
+	assert_tree(
+		"[x.y.z.w]\na = 1\n[x]\nb = 2\n",
+		"x = { b = 2, y = { z = { w = { a = 1 } } } }\n",
+	);
+	// Adding sub-tables under a table defined by a header
+	assert_tree(
+		"[fruit]\napple.color = 'red'\n[fruit.banana]\ncolor = 'yellow'\n",
+		"fruit = { apple = { color = 'red' }, banana = { color = 'yellow' } }\n",
+	);
+	// Adding sub-tables under a table defined by dotted keys
+	assert_tree(
+		"[fruit]\napple.color = 'red'\napple.taste.sweet = true\n[fruit.apple.texture]\nsmooth = true\n",
+		"fruit = { apple = { color = 'red', taste = { sweet = true }, texture = { smooth = true } } }\n",
+	);
+	// Dotted keys adding to their own tables, in any order
+	assert_tree(
+		"apple.type = 'fruit'\norange.type = 'fruit'\napple.skin = 'thin'\n",
+		"apple = { type = 'fruit', skin = 'thin' }\norange = { type = 'fruit' }\n",
+	);
+	assert_tree(
+		"a = { b.c = 1, b.d = 2 }\n",
+		"a = { b = { c = 1, d = 2 } }\n",
+	);
+}
+
+/// Test that tables can't be defined twice, and that tables defined by one header can't
+/// be extended by dotted keys or inline tables elsewhere.
+#[test]
+fn redefinition_conflicts() {
+	let cases = [
+		// Keys can't be defined twice
+		("a = 1\na = 2\n", "a"),
+		("[t]\na = 1\na = 2\n", "a"),
+		("a.b = 1\na.b = 2\n", "b"),
+		("a = 1\na.b = 2\n", "a"),
+		("a.b = 1\na = 2\n", "a"),
+		("a = { b = 1, b = 2 }\n", "b"),
+		// Tables can't be defined by two headers
+		("[a]\n[a]\n", "a"),
+		("[a]\nb = 1\n[a.c]\n[a]\n", "a"),
+		("[a.b]\n[a]\n[a.b]\n", "b"),
+		// Tables defined by dotted keys can't be defined by a header
+		("a.b = 1\n[a]\n", "a"),
+		("[fruit]\napple.color = 'red'\n[fruit.apple]\n", "apple"),
+		(
+			"[fruit]\napple.taste.sweet = true\n[fruit.apple.taste]\n",
+			"taste",
+		),
+		// Tables defined by a header can't be extended by dotted keys
+		("[a.b.c]\nz = 9\n[a]\nb.c.t = 1\n", "b"),
+		// Inline tables can't be extended at all
+		("a = {}\n[a]\n", "a"),
+		("a = { b = 1 }\n[a.c]\n", "a"),
+		("a = { b = {} }\n[a.b]\n", "a"),
+		("a = { b = 1 }\na.c = 2\n", "a"),
+		("a = { b = { c = 1 }, b.d = 2 }\n", "b"),
+		// Neither can the tables in static arrays
+		("a = [{ b = 1 }]\n[[a]]\n", "a"),
+		("a = [{ b = 1 }]\n[a.c]\n", "a"),
+	];
+
+	for (source, key) in cases {
+		let err = Toml::parse(source).unwrap_err();
+		assert_eq!(err.kind, TomlErrorKind::ReusedKey, "{source}");
+		assert_eq!(err.src.as_str(), key, "{source}");
+	}
+}
+
+/// Test that inline tables fully define their table, so nothing can be added to them
+/// later, no matter how deeply they're nested.
+#[test]
+fn inline_tables_closed() {
+	let cases = [
+		// From the spec
+		(
+			"[product]\ntype = { name = 'Nail' }\ntype.edible = false\n",
+			"type",
+		),
+		(
+			"[product]\ntype.name = 'Nail'\ntype = { edible = false }\n",
+			"type",
+		),
+		("point = { x = 1 }\npoint.y = 2\n", "point"),
+		("point = { x = 1 }\n[point]\n", "point"),
+		// Nested inline tables, and inline tables inside other tables
+		("point = { x = { y = 1 } }\npoint.x.z = 2\n", "point"),
+		("[t]\np = { x = { y = 1 } }\n[t.p.x.z]\n", "p"),
+		("a = { b = [{ c = 1 }] }\n[[a.b]]\n", "a"),
+	];
+
+	for (source, key) in cases {
+		let err = Toml::parse(source).unwrap_err();
+		assert_eq!(err.kind, TomlErrorKind::ReusedKey, "{source}");
+		assert_eq!(err.src.as_str(), key, "{source}");
+	}
+
+	// The inline table keeps its original contents when recovering from the error
+	let (toml, errors) = Toml::parse_lenient("point = { x = 1 }\npoint.y = 2\n[point.z]\n");
+	assert_eq!(errors.len(), 2);
+	assert_eq!(toml.get_table("point").unwrap().len(), 1);
+}
+
+/// Test that the `try_get_<type>` methods return `None` for missing values and values of
+/// the wrong type, while the `get_<type>` methods say which one it was.
+#[test]
+fn optional_getters() {
+	let toml = Toml::parse(concat!(
+		"string = 'hi'\n",
+		"integer = 1\n",
+		"float = 1.5\n",
+		"boolean = true\n",
+		"array = [1, 2]\n",
+		"empty = []\n",
+		"[table]\n",
+	))
+	.unwrap();
+
+	assert_eq!(toml.try_get_string("string"), Some("hi"));
+	assert_eq!(toml.try_get_integer("integer"), Some(1));
+	assert_eq!(toml.try_get_float("float"), Some(1.5));
+	assert_eq!(toml.try_get_boolean("boolean"), Some(true));
+	assert_eq!(toml.try_get_array("array").unwrap().len(), 2);
+	assert_eq!(toml.try_get_array("empty"), Some(&[][..]));
+	assert!(toml.try_get_table("table").unwrap().is_empty());
+
+	assert_eq!(toml.try_get_string("missing"), None);
+	assert_eq!(toml.try_get_integer("string"), None);
+	assert_eq!(toml.try_get_array("table"), None);
+	assert!(toml.try_get_table("array").is_none());
+
+	// The `Result` versions keep the reason
+	assert_eq!(toml.get_integer("missing"), Err(TomlGetError::InvalidKey));
+	assert!(matches!(
+		toml.get_integer("string"),
+		Err(TomlGetError::TypeMismatch(_, TomlValueType::String))
+	));
+	let array: &[TomlValue<'_>] = toml.get_array("array").unwrap();
+	assert_eq!(array, [TomlValue::Integer(1), TomlValue::Integer(2)]);
+}
+
+/// Test that `get_many` returns every value, or every failed request.
+#[test]
+fn get_many() {
+	let toml = Toml::parse(concat!(
+		"name = 'app'\n",
+		"workers = 4\n",
+		"[plugins.auth]\n",
+		"enabled = true\n",
+		"'key.with.dots' = 1.5\n",
+		"[plugins.cache]\n",
+		"size = 'large'\n",
+	))
+	.unwrap();
+
+	// Everything succeeds
+	let values = toml
+		.get_many(&[
+			("name", TomlValueType::String),
+			("workers", TomlValueType::Integer),
+			("plugins.auth.enabled", TomlValueType::Boolean),
+			("plugins.auth.'key.with.dots'", TomlValueType::Float),
+			("plugins . cache", TomlValueType::Table),
+		])
+		.unwrap();
+	assert_eq!(values.len(), 5);
+	assert_eq!(values[0].string(), Some("app"));
+	assert_eq!(values[1].integer(), Some(4));
+	assert_eq!(values[2].boolean(), Some(true));
+	assert_eq!(values[3].float(), Some(1.5));
+	assert!(values[4].table().is_some());
+
+	// Some requests fail, and all of them are reported
+	let errors = toml
+		.get_many(&[
+			("name", TomlValueType::String),
+			("workers", TomlValueType::String),
+			("plugins.auth.enabled", TomlValueType::Boolean),
+			("plugins.metrics.enabled", TomlValueType::Boolean),
+			("plugins.cache.size", TomlValueType::Integer),
+			("name.first", TomlValueType::String),
+			("not a key", TomlValueType::String),
+		])
+		.unwrap_err();
+	let errors: Vec<_> = errors
+		.iter()
+		.map(|(path, err)| {
+			let ty = match err {
+				TomlGetError::InvalidKey => None,
+				TomlGetError::TypeMismatch(_, ty) => Some(*ty),
+			};
+			(*path, ty)
+		})
+		.collect();
+	assert_eq!(
+		errors,
+		[
+			("workers", Some(TomlValueType::Integer)),
+			("plugins.metrics.enabled", None),
+			("plugins.cache.size", Some(TomlValueType::String)),
+			("name.first", Some(TomlValueType::String)),
+			("not a key", None),
+		]
+	);
+
+	// The same path can be requested more than once
+	let values = toml
+		.get_many(&[
+			("workers", TomlValueType::Integer),
+			("workers", TomlValueType::Integer),
+		])
+		.unwrap();
+	assert_eq!(values, [&TomlValue::Integer(4), &TomlValue::Integer(4)]);
+	let errors = toml
+		.get_many(&[
+			("missing", TomlValueType::Integer),
+			("missing", TomlValueType::Integer),
+		])
+		.unwrap_err();
+	assert_eq!(errors.len(), 2);
+
+	// No requests is trivially fine
+	assert!(toml.get_many(&[]).unwrap().is_empty());
+}
+
+/// Test that the fast hasher tells keys apart, including ones that only differ after the
+/// first 8 bytes, and that tables work the same with either hasher.
+#[test]
+fn fast_hasher() {
+	use {boml::table::TableHasher, std::hash::BuildHasher};
+
+	let hasher = TableHasher::fast();
+	let keys = [
+		"",
+		"a",
+		"b",
+		"ab",
+		"ba",
+		"package-1",
+		"package-2",
+		"package-10",
+	];
+	for (idx, first) in keys.iter().enumerate() {
+		assert_eq!(hasher.hash_one(first), hasher.hash_one(first));
+		for second in &keys[idx + 1..] {
+			assert_ne!(
+				hasher.hash_one(first),
+				hasher.hash_one(second),
+				"{first} {second}"
+			);
+		}
+	}
+
+	let mut source = String::new();
+	for idx in 0..1000 {
+		source.push_str(&format!("key-{idx} = {idx}\n"));
+	}
+	source.push_str("[nested]\nkey = 1\ndotted.key = { inline = 2 }\n");
+	for fast in [false, true] {
+		let options = ParseOptions::new().fast_hash(fast);
+		let toml = Toml::parse_with(&source, &options).unwrap();
+		assert_eq!(toml.len(), 1001);
+		for idx in 0..1000 {
+			assert_eq!(toml.get_integer(&format!("key-{idx}")), Ok(idx));
+		}
+
+		// Every table in the document uses the same hasher
+		let nested = toml.get_table("nested").unwrap();
+		let dotted = nested.get_table("dotted").unwrap();
+		let inline = dotted.get_table("key").unwrap();
+		assert_eq!(inline.get_integer("inline"), Ok(2));
+		for table in [&*toml, nested, dotted, inline] {
+			assert_eq!(table.hasher().is_fast(), fast);
+		}
+	}
+}
+
+/// Test that tables know where their keys were written.
+#[test]
+fn key_spans() {
+	let source = concat!(
+		"port = 8080\n",
+		"'quoted key' = 1\n",
+		"\"escaped\\tkey\" = 2\n",
+		"dotted.first = 3\n",
+		"dotted.second = 4\n",
+		"[server.tls]\n",
+		"enabled = true\n",
+		"[server]\n",
+		"host = 'localhost'\n",
+	);
+	let toml = Toml::parse(source).unwrap();
+
+	let span = toml.key_span("port").unwrap();
+	assert_eq!(span.as_str(), "port");
+	assert_eq!((span.start, span.end), (0, 3));
+	assert_eq!(toml.key_span("quoted key").unwrap().as_str(), "quoted key");
+	assert_eq!(
+		toml.key_span("escaped\tkey").unwrap().as_str(),
+		"escaped\\tkey"
+	);
+	assert!(toml.key_span("missing").is_none());
+
+	// Tables named more than once point at the first name
+	let dotted = toml.key_span("dotted").unwrap();
+	assert_eq!(dotted.as_str(), "dotted");
+	assert_eq!(dotted.line_col(), (4, 1));
+	let server = toml.key_span("server").unwrap();
+	assert_eq!(server.line_col(), (6, 2));
+
+	let server = toml.get_table("server").unwrap();
+	assert_eq!(server.key_span("host").unwrap().line_col(), (9, 1));
+	let tls = server.key_span("tls").unwrap();
+	assert_eq!(tls.as_str(), "tls");
+	assert_eq!(tls.line_col(), (6, 9));
+
+	let mut entries: Vec<_> = toml
+		.get_table("dotted")
+		.unwrap()
+		.entries_with_spans()
+		.map(|(key, span, value)| (key, span.unwrap().line_col(), value.integer().unwrap()))
+		.collect();
+	entries.sort();
+	assert_eq!(entries, [("first", (4, 8), 3), ("second", (5, 8), 4)]);
+}
+
+/// Test that tables defined by headers know where their header is, and that each table
+/// in an array of tables has its own.
+#[test]
+fn header_spans() {
+	let source = concat!(
+		"inline = { a = 1 }\n",
+		"dotted.a = 1\n",
+		"[a.b]\n",
+		"[ a ] # Defined after its sub-table\n",
+		"[[fruit]]\n",
+		"name = 'apple'\n",
+		"[[fruit.variety]]\n",
+		"[[fruit]]\n",
+		"name = 'banana'\n",
+		"[[ fruit.variety ]]\n",
+	);
+	let toml = Toml::parse(source).unwrap();
+
+	assert!(toml.header_span().is_none());
+	assert!(toml.get_table("inline").unwrap().header_span().is_none());
+	assert!(toml.get_table("dotted").unwrap().header_span().is_none());
+
+	let a = toml.get_table("a").unwrap();
+	assert_eq!(a.header_span().unwrap().as_str(), "[ a ]");
+	assert_eq!(a.header_span().unwrap().line_col(), (4, 1));
+	let b = a.get_table("b").unwrap();
+	assert_eq!(b.header_span().unwrap().as_str(), "[a.b]");
+
+	let headers: Vec<_> = toml
+		.get_array("fruit")
+		.unwrap()
+		.iter()
+		.map(|fruit| {
+			let fruit = fruit.table().unwrap();
+			let variety = fruit.get_array("variety").unwrap()[0].table().unwrap();
+			(
+				fruit.header_span().unwrap().line_col(),
+				variety.header_span().unwrap().to_str(),
+				variety.header_span().unwrap().line_col(),
+			)
+		})
+		.collect();
+	assert_eq!(
+		headers,
+		[
+			((5, 1), "[[fruit.variety]]", (7, 1)),
+			((8, 1), "[[ fruit.variety ]]", (10, 1)),
+		]
+	);
+}
+
+/// Test that cloned documents have the same values as the original, and still point into
+/// the same source.
+#[test]
+fn clone() {
+	let source = concat!(
+		"title = \"escaped\\tstring\"\n",
+		"numbers = [1, 2.5, [true, 'nested']]\n",
+		"[server]\n",
+		"inline = { a = { b = 1 } }\n",
+		"[[server.listener]]\n",
+		"port = 80\n",
+		"[[server.listener]]\n",
+		"port = 443\n",
+	);
+	let toml = Toml::parse(source).unwrap();
+	let clone = toml.clone();
+
+	assert_eq!(*clone, *toml);
+	assert_eq!(clone.source(), toml.source());
+	assert_eq!(clone.get_string("title"), Ok("escaped\tstring"));
+
+	let server = toml.get_table("server").unwrap();
+	let cloned_server = server.clone();
+	assert_eq!(&cloned_server, server);
+	let listeners = cloned_server.get_array("listener").unwrap();
+	assert_eq!(listeners, server.get_array("listener").unwrap());
+	assert_eq!(
+		listeners[1]
+			.table()
+			.unwrap()
+			.header_span()
+			.unwrap()
+			.line_col(),
+		(7, 1)
+	);
+	assert_eq!(
+		cloned_server.key_span("inline").unwrap().start,
+		server.key_span("inline").unwrap().start
+	);
+
+	// Clones outlive the `Toml` they came from, as long as the source is still around
+	let numbers = toml.get("numbers").unwrap().clone();
+	drop(toml);
+	assert_eq!(numbers, clone.get("numbers").unwrap().clone());
+}
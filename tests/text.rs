@@ -0,0 +1,141 @@
+use boml::{
+	parser,
+	text::{self, Span, Text},
+};
+
+/// Test that checkpoints and speculation rewind the text correctly.
+#[test]
+fn speculation() {
+	let mut text = Text {
+		text: "key = 'unclosed",
+		..Default::default()
+	};
+
+	let start = text.checkpoint();
+	text.idx = 4;
+	text.rewind(start);
+	assert_eq!(text.idx, 0);
+
+	// Successful speculation keeps its progress
+	let result: Result<(), ()> = text.speculate(|text| {
+		text.idx = 6;
+		Ok(())
+	});
+	assert!(result.is_ok());
+	assert_eq!(text.idx, 6);
+
+	// Failed speculation rewinds, even after consuming part of a string
+	let result = text.speculate(|text| {
+		let string = parser::parse_string(text);
+		text.idx += 4;
+		string
+	});
+	assert!(result.is_err());
+	assert_eq!(text.idx, 6);
+
+	// Nested speculation only rewinds as far as its own start
+	let result: Result<(), ()> = text.speculate(|text| {
+		text.idx += 2;
+		let inner: Result<(), ()> = text.speculate(|text| {
+			text.idx += 3;
+			Err(())
+		});
+		assert!(inner.is_err());
+		assert_eq!(text.idx, 8);
+
+		text.idx += 1;
+		Err(())
+	});
+	assert!(result.is_err());
+	assert_eq!(text.idx, 6);
+}
+
+/// Test that spans which start or end in the middle of a character are widened instead
+/// of panicking.
+#[test]
+fn span_char_boundaries() {
+	let source = "a = 🦀é";
+	let span = |start, end| Span { start, end, source };
+
+	assert_eq!(span(4, 7).try_as_str(), Some("🦀"));
+	assert_eq!(span(4, 7).as_str(), "🦀");
+	for (start, end) in [(4, 4), (4, 5), (5, 7), (6, 8)] {
+		assert_eq!(span(start, end).try_as_str(), None, "{start}..={end}");
+	}
+	assert_eq!(span(4, 4).as_str(), "🦀");
+	assert_eq!(span(6, 8).as_str(), "🦀é");
+	assert_eq!(span(6, 8).as_bytes(), &source.as_bytes()[6..=8]);
+
+	// Past the end of the source
+	assert_eq!(span(8, 20).try_as_str(), None);
+	assert_eq!(span(8, 20).as_str(), "é");
+	assert_eq!(span(8, 20).as_bytes(), "é".as_bytes());
+	assert_eq!(span(30, 40).as_bytes(), b"");
+
+	// Empty spans
+	assert_eq!(span(5, 4).try_as_str(), Some(""));
+	assert_eq!(span(5, 4).as_str(), "");
+}
+
+/// Test that excerpts at the edges of the text don't overflow.
+#[test]
+fn excerpt_edges() {
+	let empty = Text::default();
+	assert_eq!(empty.excerpt(..).as_str(), "");
+	assert_eq!(empty.excerpt(0..0).as_str(), "");
+
+	let text = Text {
+		text: "\nkey",
+		..Default::default()
+	};
+	assert!(text.excerpt(0..0).is_empty());
+	assert_eq!(text.excerpt(0..0).as_str(), "");
+	assert_eq!(text.excerpt(..).as_str(), "\nkey");
+	assert_eq!(text.excerpt(1..).as_str(), "key");
+	assert_eq!(text.excerpt(..).find_next_whitespace_or_newline(), Some(0));
+}
+
+/// Test that whitespace is skipped correctly around the 8-byte fast path, with every mix of
+/// whitespace, and with bytes that are one bit away from whitespace after it.
+#[test]
+fn skip_whitespace() {
+	for len in 0..40 {
+		for fill in [" ", "\t", " \t", "\n", " \r\n\t"] {
+			let indent: String = fill.chars().cycle().take(len).collect();
+			for tail in [
+				"",
+				"x",
+				"\tx",
+				"\n x",
+				"\r\n        \t  x",
+				"!",
+				"\x08",
+				"\x0b",
+				"é",
+			] {
+				let source = format!("{indent}{tail}");
+				let mut text = Text {
+					text: &source,
+					..Default::default()
+				};
+				text.skip_whitespace_and_newlines();
+				let expected = source.trim_start_matches([' ', '\t', '\n', '\r']);
+				assert_eq!(&source[text.idx..], expected, "{source:?}");
+
+				text.idx = 0;
+				text.skip_whitespace();
+				let expected = source.trim_start_matches([' ', '\t']);
+				assert_eq!(&source[text.idx..], expected, "{source:?}");
+			}
+		}
+	}
+}
+
+/// Test that the bare key lookup table allows exactly the bytes the spec does.
+#[test]
+fn bare_key_bytes() {
+	for byte in 0..=u8::MAX {
+		let expected = byte.is_ascii_alphanumeric() || byte == b'-' || byte == b'_';
+		assert_eq!(text::is_bare_key_byte(byte), expected, "{byte}");
+	}
+}
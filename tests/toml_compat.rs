@@ -0,0 +1,85 @@
+#![cfg(feature = "toml-compat")]
+
+use boml::{prelude::*, toml_compat::UnsupportedDateTime};
+
+/// A manifest that uses every kind of value boml supports.
+const MANIFEST: &str = r#"
+[package]
+name = "example"
+version = "1.2.3"
+authors = ["Someone <someone@example.com>", 'Someone Else']
+description = """
+A multiline\tdescription,
+with escapes: é \" \\
+"""
+keywords = []
+edition = 2021
+publish = false
+
+[package.metadata.docs]
+all-features = true
+rustdoc-args = ["--cfg", "docsrs"]
+
+[features]
+default = ["std"]
+std = []
+
+[dependencies]
+serde = { version = "1.0", features = ["derive"], optional = true }
+"quoted key" = { path = "../quoted", default-features = false }
+
+[profile.release]
+opt-level = 3
+lto = "fat"
+debug = 0x10
+split-debuginfo = 'packed'
+overflow-checks = -0
+
+[profile.bench]
+inherits = "release"
+weights = [1.5, -2e3, inf, 0.0, [1, [2.5]]]
+
+[[bin]]
+name = "first"
+path = "src/first.rs"
+
+[[bin]]
+name = "second"
+test = true
+"#;
+
+/// Test that converting to the `toml` crate's values gives the same result as parsing
+/// with it, and that converting back gives the same result as parsing with boml.
+#[test]
+fn round_trip() {
+	let toml = Toml::parse(MANIFEST).unwrap();
+	let expected: toml::Table = toml::from_str(MANIFEST).unwrap();
+
+	let converted: toml::Table = (&*toml).try_into().unwrap();
+	assert_eq!(converted, expected);
+
+	let back = TomlTable::try_from(&converted).unwrap();
+	assert_eq!(&back, &*toml);
+	assert_eq!(
+		back.get_table("package").unwrap().get_string("description"),
+		Ok("A multiline\tdescription,\nwith escapes: \u{e9} \" \\\n")
+	);
+	assert!(back.key_span("package").is_none());
+	assert!(back.entries_with_spans().all(|(_, span, _)| span.is_none()));
+
+	let value: toml::Value = toml.get("bin").unwrap().try_into().unwrap();
+	assert_eq!(value, expected["bin"]);
+	assert_eq!(
+		TomlValue::try_from(&value).unwrap(),
+		*toml.get("bin").unwrap()
+	);
+}
+
+/// Test that date and time values are rejected in both directions.
+#[test]
+fn date_times() {
+	let value: toml::Table = toml::from_str("a = [1979-05-27]").unwrap();
+	assert_eq!(TomlTable::try_from(&value), Err(UnsupportedDateTime));
+	let result: Result<toml::Value, _> = (&TomlValue::Array(vec![TomlValue::LocalTime])).try_into();
+	assert_eq!(result, Err(UnsupportedDateTime));
+}